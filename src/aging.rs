@@ -0,0 +1,193 @@
+// Aging: every page keeps a shift register, on each clock tick the referenced bit gets shifted in
+// from the left, so the page with the lowest counter is the one least recently used (roughly)
+use {exit_on_error, parse_file, parse_nonzero, parse_number};
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+// What the comparison runs use when nobody tells us otherwise
+pub const DEFAULT_TICK : usize = 4;
+pub const DEFAULT_BITS : u32 = 8;
+
+// Aging parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct AGING_Params {
+    pub total_frames : usize,
+    // Shift the counters every this many accesses
+    pub tick : usize,
+    // Counter width, 8, 16 or 32
+    pub bits : u32,
+    pub access_string : String,
+}
+
+impl AGING_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> AGING_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let frames = exit_on_error(parse_number("frame count", args.get(1)));
+        let tick = exit_on_error(parse_nonzero("tick", args.get(2)));
+        let bits = exit_on_error(parse_bits(args.get(3)));
+        let file = exit_on_error(parse_file(args.get(4)));
+        AGING_Params {
+            total_frames : frames,
+            tick,
+            bits,
+            access_string : file,
+        }
+    }
+}
+
+pub fn parse_bits(bits_string : Option<&String>) -> Result<u32, String> {
+    match parse_number("counter bits", bits_string)? {
+        8 => Ok(8),
+        16 => Ok(16),
+        32 => Ok(32),
+        n => Err(format!("Counter bits must be 8, 16 or 32, not {}", n)),
+    }
+}
+
+pub fn process_page_requests(tick : usize , bits : u32 , total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<Page>) -> Result<Vec<AccessResult>, String> {
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    // The referenced bit goes in at the top of the register
+    let top_bit = 1u32 << (bits - 1);
+    let mut pins = Pins::new();
+
+    for (clock, access) in accesses.iter().enumerate() {
+        pins.update(access);
+        // A freed page gives its frame up, the clock still ticks
+        if access.access_type == AccessType::Free {
+            v_memory.retain(|page| page.number != access.frame_number);
+            results.push(AccessResult::Freed);
+        } else if let Some(index) = contains_page(access.frame_number, &v_memory) {
+            v_memory[index].referenced = true;
+            results.push(AccessResult::Hit);
+        } else if v_memory.len() < total_physical_pages {
+            // We have space, so it's a simple miss
+            v_memory.push(Page { number : access.frame_number, referenced : true, counter : 0 });
+            results.push(AccessResult::MissSimple);
+        } else {
+            // Lowest counter goes, if they're tied then one that wasn't touched since the last tick,
+            // and after that whichever frame comes first
+            pins.check_evictable(total_physical_pages, access.frame_number)?;
+            let index = v_memory.iter()
+                .enumerate()
+                .filter(|&(_, page)| !pins.contains(page.number))
+                .min_by_key(|&(i, page)| (page.counter, page.referenced, i))
+                .map(|(i, _)| i)
+                .unwrap();
+            results.push(AccessResult::MissReplace(
+                MissReplacement::new(
+                    v_memory[index].number,
+                    index,
+                    access.frame_number)));
+            v_memory[index] = Page { number : access.frame_number, referenced : true, counter : 0 };
+        }
+
+        // Clock tick! Shift every register right and put the referenced bit in at the top
+        if (clock + 1) % tick == 0 {
+            for page in v_memory.iter_mut() {
+                page.counter >>= 1;
+                if page.referenced {
+                    page.counter |= top_bit;
+                }
+                page.referenced = false;
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+fn contains_page(page_num : usize , collection : &[Page]) -> Option<usize> {
+    collection.iter().position(|item| item.number == page_num)
+}
+
+// Second Chance's page with a whole register of reference history instead of a single bit
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub number : usize,
+    pub referenced : bool,
+    pub counter : u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(1, 8, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 W:1 R:1 W:1"));
+        let total_pages = 2;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(1, 8, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        for result in results.iter().skip(1) {
+            assert_eq!(*result , AccessResult::Hit);
+        }
+        assert_eq!(get_total_faults(&results) , 1);
+    }
+
+    #[test]
+    fn tick_of_one_evicts_least_recently_used() {
+        // Counters after R:1 R:2 R:3 R:1 are 1: 10010000, 2: 00100000, 3: 01000000
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1 R:4"));
+        let total_pages = 3;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(1, 8, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[3] , AccessResult::Hit);
+
+        // Replaced R:2 at index 1 with R:4
+        let mr = MissReplacement::new(2, 1, 4);
+        assert_eq!(results[4] , AccessResult::MissReplace(mr));
+    }
+
+    #[test]
+    fn pages_touched_within_the_same_tick_look_the_same() {
+        // With a tick of 4 nobody has been shifted yet when R:4 comes in, so all counters are 0 and
+        // every page is referenced, first frame goes
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4"));
+        let total_pages = 3;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(4, 8, total_pages, accesses, v_memory).unwrap();
+        let mr = MissReplacement::new(1, 0, 4);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+    }
+
+    #[test]
+    fn narrow_counter_forgets_old_references() {
+        // Page 1 is touched after page 2, then page 3 is touched 9 times in a row
+        let accesses = MemoryAccess::create(String::from(
+            "R:1 R:2 R:3 R:1 R:3 R:3 R:3 R:3 R:3 R:3 R:3 R:3 R:3 R:4"));
+
+        // With 8 bits both 1 and 2 have been shifted out to 0, so the first frame goes
+        let v_memory : Vec<Page> = Vec::with_capacity(3);
+        let results = process_page_requests(1, 8, 3, accesses.clone(), v_memory).unwrap();
+        let mr = MissReplacement::new(1, 0, 4);
+        assert_eq!(results[13] , AccessResult::MissReplace(mr));
+
+        // With 16 bits we still remember that 2 is the older one
+        let v_memory : Vec<Page> = Vec::with_capacity(3);
+        let results = process_page_requests(1, 16, 3, accesses, v_memory).unwrap();
+        let mr = MissReplacement::new(2, 1, 4);
+        assert_eq!(results[13] , AccessResult::MissReplace(mr));
+    }
+
+    #[test]
+    fn counter_bits_are_checked() {
+        assert_eq!(parse_bits(Some(&String::from("16"))) , Ok(16));
+        assert!(parse_bits(Some(&String::from("12"))).is_err());
+        assert!(parse_bits(None).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+// Characterizes a trace before picking frames or tau, none of this depends on an algorithm
+use std::collections::{BTreeMap, HashMap};
+use {exit_on_error, parse_file};
+use {AccessType, MemoryAccess};
+
+// How many of the most accessed pages get listed
+pub const HOTTEST_PAGES : usize = 10;
+
+// Analyze parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct ANALYZE_Params {
+    pub access_string : String,
+}
+
+impl ANALYZE_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> ANALYZE_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let file = exit_on_error(parse_file(args.get(1)));
+        ANALYZE_Params {
+            access_string : file,
+        }
+    }
+}
+
+// Reuse distance is how many other distinct pages were touched since the last access to this page,
+// so a page with distance d hits under LRU as long as there are more than d frames
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReuseHistogram {
+    // Distance -> number of accesses with that distance
+    pub distances : BTreeMap<usize, usize>,
+    // First touches have an infinite distance
+    pub cold : usize,
+}
+
+pub fn reuse_distances(accesses : &[MemoryAccess]) -> ReuseHistogram {
+    // Most recently used page is at the end
+    let mut stack : Vec<usize> = Vec::new();
+    let mut histogram = ReuseHistogram { distances : BTreeMap::new(), cold : 0 };
+    for access in accesses {
+        match stack.iter().rposition(|&p| p == access.frame_number) {
+            Some(i) => {
+                let distance = stack.len() - 1 - i;
+                *histogram.distances.entry(distance).or_insert(0) += 1;
+                stack.remove(i);
+            }
+            None => histogram.cold += 1,
+        }
+        stack.push(access.frame_number);
+    }
+    histogram
+}
+
+// Working set size over sliding windows of a given length
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkingSet {
+    pub window : usize,
+    pub average : f64,
+    pub max : usize,
+}
+
+pub fn working_set(accesses : &[MemoryAccess] , window : usize) -> WorkingSet {
+    let mut counts : HashMap<usize, usize> = HashMap::new();
+    let mut total = 0;
+    let mut max = 0;
+    for (t, access) in accesses.iter().enumerate() {
+        *counts.entry(access.frame_number).or_insert(0) += 1;
+        if t >= window {
+            let old = accesses[t - window].frame_number;
+            let count = counts.get_mut(&old).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(&old);
+            }
+        }
+        total += counts.len();
+        max = max.max(counts.len());
+    }
+    WorkingSet {
+        window,
+        average : if accesses.is_empty() { 0.0 } else { total as f64 / accesses.len() as f64 },
+        max,
+    }
+}
+
+// Window lengths 1, 2, 4... up to the length of the trace
+pub fn window_lengths(trace_length : usize) -> Vec<usize> {
+    let mut windows = Vec::new();
+    let mut window = 1;
+    while window < trace_length {
+        windows.push(window);
+        window *= 2;
+    }
+    if trace_length > 0 {
+        windows.push(trace_length);
+    }
+    windows
+}
+
+// Read and write counts for a single page
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageCount {
+    pub page : usize,
+    pub reads : usize,
+    pub writes : usize,
+}
+
+impl PageCount {
+    pub fn total(&self) -> usize {
+        self.reads + self.writes
+    }
+}
+
+// Every page with its access counts, most accessed first, ties go to the lower page number
+pub fn page_counts(accesses : &[MemoryAccess]) -> Vec<PageCount> {
+    let mut counts : BTreeMap<usize, PageCount> = BTreeMap::new();
+    for access in accesses {
+        let count = counts.entry(access.frame_number)
+            .or_insert(PageCount { page : access.frame_number, reads : 0, writes : 0 });
+        match access.access_type {
+            // Locking and unlocking read the page
+            AccessType::Read | AccessType::Lock | AccessType::Unlock => count.reads += 1,
+            AccessType::Write => count.writes += 1,
+            AccessType::Free => (),
+        }
+    }
+    let mut counts : Vec<PageCount> = counts.into_values().collect();
+    counts.sort_by_key(|c| std::cmp::Reverse(c.total()));
+    counts
+}
+
+// Everything the analyze command prints
+pub struct Analysis {
+    pub accesses : usize,
+    pub distinct_pages : usize,
+    pub reads : usize,
+    pub writes : usize,
+    pub reuse : ReuseHistogram,
+    pub working_sets : Vec<WorkingSet>,
+    pub hottest : Vec<PageCount>,
+}
+
+impl Analysis {
+    // Constructor running every analysis over the parsed trace, frees aren't accesses so they're
+    // left out of all of it
+    pub fn new(accesses : &[MemoryAccess]) -> Analysis {
+        let accesses : Vec<MemoryAccess> = accesses.iter()
+            .filter(|a| a.access_type != AccessType::Free)
+            .cloned()
+            .collect();
+        let accesses = &accesses[..];
+        let counts = page_counts(accesses);
+        let writes = counts.iter().map(|c| c.writes).sum();
+        Analysis {
+            accesses : accesses.len(),
+            distinct_pages : counts.len(),
+            reads : accesses.len() - writes,
+            writes,
+            reuse : reuse_distances(accesses),
+            working_sets : window_lengths(accesses.len()).into_iter()
+                .map(|w| working_set(accesses, w))
+                .collect(),
+            hottest : counts.into_iter().take(HOTTEST_PAGES).collect(),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let percent = |n : usize| if self.accesses == 0 { 0.0 } else { 100.0 * n as f64 / self.accesses as f64 };
+        let mut text = format!("Accesses: {}\nDistinct pages: {}\n", self.accesses, self.distinct_pages);
+        text.push_str(&format!("Reads: {} ({:.1}%)\nWrites: {} ({:.1}%)\n",
+                               self.reads, percent(self.reads), self.writes, percent(self.writes)));
+        text.push_str("\nReuse distance histogram\n");
+        text.push_str(&format!("{:>10} {:>8} {:>8}\n", "distance", "count", "percent"));
+        for (distance, count) in &self.reuse.distances {
+            text.push_str(&format!("{:>10} {:>8} {:>7.1}%\n", distance, count, percent(*count)));
+        }
+        text.push_str(&format!("{:>10} {:>8} {:>7.1}%\n", "cold", self.reuse.cold, percent(self.reuse.cold)));
+        text.push_str("\nWorking set size\n");
+        text.push_str(&format!("{:>10} {:>8} {:>8}\n", "window", "average", "max"));
+        for ws in &self.working_sets {
+            text.push_str(&format!("{:>10} {:>8.2} {:>8}\n", ws.window, ws.average, ws.max));
+        }
+        text.push_str("\nHottest pages\n");
+        text.push_str(&format!("{:>10} {:>8} {:>8} {:>8}\n", "page", "total", "reads", "writes"));
+        for c in &self.hottest {
+            text.push_str(&format!("{:>10} {:>8} {:>8} {:>8}\n", c.page, c.total(), c.reads, c.writes));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuse_distance_counts_distinct_pages_in_between() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:2 R:3 R:1 R:3"));
+        let histogram = reuse_distances(&accesses);
+        assert_eq!(histogram.cold , 3);
+        // R:2 straight after R:2
+        assert_eq!(histogram.distances.get(&0) , Some(&1));
+        // R:3 with only 1 in between
+        assert_eq!(histogram.distances.get(&1) , Some(&1));
+        // R:1 with 2 and 3 in between, the repeated 2 only counts once
+        assert_eq!(histogram.distances.get(&2) , Some(&1));
+    }
+
+    #[test]
+    fn working_set_counts_distinct_pages_per_window() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1 R:2 R:3 R:3"));
+        let ws = working_set(&accesses, 2);
+        assert_eq!(ws.max , 2);
+        // 1, 2, 2, 2, 2, 1
+        assert_eq!(ws.average , 10.0 / 6.0);
+        assert_eq!(working_set(&accesses, 6).max , 3);
+    }
+
+    #[test]
+    fn window_lengths_double_and_end_at_trace_length() {
+        assert_eq!(window_lengths(10) , vec![1, 2, 4, 8, 10]);
+        assert_eq!(window_lengths(8) , vec![1, 2, 4, 8]);
+        assert_eq!(window_lengths(0) , Vec::<usize>::new());
+    }
+
+    #[test]
+    fn hottest_pages_and_read_write_mix() {
+        let accesses = MemoryAccess::create(String::from("R:1 W:2 W:2 R:3 R:2 W:1"));
+        let analysis = Analysis::new(&accesses);
+        assert_eq!(analysis.distinct_pages , 3);
+        assert_eq!(analysis.reads , 3);
+        assert_eq!(analysis.writes , 3);
+        assert_eq!(analysis.hottest[0] , PageCount { page : 2, reads : 1, writes : 2 });
+        assert_eq!(analysis.hottest[1] , PageCount { page : 1, reads : 1, writes : 1 });
+        assert_eq!(analysis.hottest[2] , PageCount { page : 3, reads : 1, writes : 0 });
+    }
+}
@@ -0,0 +1,214 @@
+// Adaptive Replacement Cache: T1 holds pages seen once recently, T2 pages seen at least twice.
+// B1 and B2 are ghosts of what got evicted from each, they hold no frames, only page numbers.
+// A hit on a ghost tells us which side we were too stingy with, and the target size p for T1
+// moves towards it.
+use std::collections::VecDeque;
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+pub fn process_page_requests(total_physical_pages : usize , accesses : Vec<MemoryAccess> , v_memory : Vec<usize>) -> Result<Vec<AccessResult>, String> {
+    Ok(process_page_requests_with_target(total_physical_pages, accesses, v_memory)?.0)
+}
+
+// Same as above, but also hands back the target p after every access
+pub fn process_page_requests_with_target(total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<usize>) -> Result<(Vec<AccessResult>, Vec<usize>), String> {
+    let c = total_physical_pages;
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut targets : Vec<usize> = Vec::with_capacity(accesses.len());
+    let mut lists = Lists::default();
+    let mut p = 0;
+    let mut pins = Pins::new();
+
+    for access in accesses.iter() {
+        pins.update(access);
+        let x = access.frame_number;
+        let mut evicted = None;
+        // A freed page is forgotten completely, ghosts included, so coming back is a plain miss
+        if access.access_type == AccessType::Free {
+            for list in [&mut lists.t1, &mut lists.t2, &mut lists.b1, &mut lists.b2] {
+                remove(list, x);
+            }
+            v_memory.retain(|&f| f != x);
+            results.push(AccessResult::Freed);
+            targets.push(p);
+            continue;
+        }
+        // Only ever fails on a fault, a resident page is never one of the pinned ones crowding it out
+        pins.check_evictable(c, x)?;
+
+        if remove(&mut lists.t1, x) || remove(&mut lists.t2, x) {
+            // Case I, a hit in either list makes it frequent
+            lists.t2.push_back(x);
+            results.push(AccessResult::Hit);
+            targets.push(p);
+            continue;
+        } else if remove(&mut lists.b1, x) {
+            // Case II, we shouldn't have evicted it from T1, so T1 gets more room
+            let delta = (lists.b2.len() / (lists.b1.len() + 1)).max(1);
+            p = (p + delta).min(c);
+            evicted = replace(&mut lists, c, false, p, &pins);
+            lists.t2.push_back(x);
+        } else if remove(&mut lists.b2, x) {
+            // Case III, we shouldn't have evicted it from T2, so T2 gets more room
+            let delta = (lists.b1.len() / (lists.b2.len() + 1)).max(1);
+            p = p.saturating_sub(delta);
+            evicted = replace(&mut lists, c, true, p, &pins);
+            lists.t2.push_back(x);
+        } else {
+            // Case IV, never seen it (or forgot it)
+            let l1 = lists.t1.len() + lists.b1.len();
+            let l2 = lists.t2.len() + lists.b2.len();
+            if l1 == c {
+                if lists.t1.len() < c {
+                    lists.b1.pop_front();
+                    evicted = replace(&mut lists, c, false, p, &pins);
+                } else {
+                    // B1 is empty and T1 fills the cache, its LRU goes for good
+                    evicted = pins.pop_unpinned(&mut lists.t1);
+                }
+            } else if l1 + l2 >= c {
+                if l1 + l2 == 2 * c {
+                    lists.b2.pop_front();
+                }
+                evicted = replace(&mut lists, c, false, p, &pins);
+            }
+            lists.t1.push_back(x);
+        }
+
+        match evicted {
+            Some(page) => {
+                let index = v_memory.iter().position(|&f| f == page).unwrap();
+                results.push(AccessResult::MissReplace(MissReplacement::new(page, index, x)));
+                v_memory[index] = x;
+            }
+            None => {
+                v_memory.push(x);
+                results.push(AccessResult::MissSimple);
+            }
+        }
+        targets.push(p);
+    }
+
+    Ok((results, targets))
+}
+
+// The four lists, LRU at the front and MRU at the back
+#[derive(Debug, Clone, Default)]
+pub struct Lists {
+    pub t1 : VecDeque<usize>,
+    pub t2 : VecDeque<usize>,
+    pub b1 : VecDeque<usize>,
+    pub b2 : VecDeque<usize>,
+}
+
+// Takes the page out of the list if it's there
+fn remove(list : &mut VecDeque<usize> , page : usize) -> bool {
+    match list.iter().position(|&p| p == page) {
+        Some(i) => {
+            list.remove(i);
+            true
+        }
+        None => false,
+    }
+}
+
+// REPLACE from the paper, pushes the LRU page of T1 or T2 down to its ghost list and gives back
+// the page that lost its frame, nothing if the cache isn't full yet. Pinned pages are passed over,
+// and if the list it should come from has nothing but pinned pages the other one gives one up.
+fn replace(lists : &mut Lists , c : usize , in_b2 : bool , p : usize , pins : &Pins) -> Option<usize> {
+    let t1 = lists.t1.len();
+    if t1 + lists.t2.len() < c {
+        return None;
+    }
+    let from_t1 = t1 >= 1 && ((in_b2 && t1 == p) || t1 > p) || lists.t2.is_empty();
+    let (first, second) = if from_t1 { (&mut lists.t1, &mut lists.t2) } else { (&mut lists.t2, &mut lists.t1) };
+    let (page, from_t1) = match pins.pop_unpinned(first) {
+        Some(page) => (page, from_t1),
+        None => (pins.pop_unpinned(second).expect("every frame is pinned"), !from_t1),
+    };
+    if from_t1 {
+        lists.b1.push_back(page);
+    } else {
+        lists.b2.push_back(page);
+    }
+    Some(page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn third_miss_is_miss_replace() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+
+        // Replaced R:1 at index 0 with R:3
+        let mr = MissReplacement::new(1, 0, 3);
+        assert_eq!(results[2] , AccessResult::MissReplace(mr));
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 W:1 R:1 W:1"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        for result in results.iter().skip(1) {
+            assert_eq!(*result , AccessResult::Hit);
+        }
+    }
+
+    #[test]
+    fn ghost_hits_move_the_target_back_and_forth() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 R:2 R:3 R:2 R:1"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let (results, targets) = process_page_requests_with_target(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::Hit);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+
+        // 2 is only in T1 so it goes to B1 to make room for 3
+        let mr = MissReplacement::new(2, 1, 3);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+
+        // 2 comes back from B1, T1 should grow so p goes up and T2 gives up 1
+        let mr = MissReplacement::new(1, 0, 2);
+        assert_eq!(results[4] , AccessResult::MissReplace(mr));
+
+        // 1 comes back from B2, so p goes down again and T1 gives up 3
+        let mr = MissReplacement::new(3, 1, 1);
+        assert_eq!(results[5] , AccessResult::MissReplace(mr));
+
+        assert_eq!(targets , vec![0, 0, 0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn frequent_pages_survive_a_scan() {
+        // 1 and 2 are used twice, then a long scan goes by, they should still be there
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1 R:2 R:3 R:4 R:5 R:6 R:1 R:2"));
+        let total_pages = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[8] , AccessResult::Hit);
+        assert_eq!(results[9] , AccessResult::Hit);
+    }
+}
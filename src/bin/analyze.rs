@@ -0,0 +1,15 @@
+extern crate a02;
+
+use a02::*;
+use a02::analyze::*;
+
+fn main() -> std::io::Result<()> {
+    let params = ANALYZE_Params::get();
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+
+    print!("{}", Analysis::new(&accesses).to_text());
+
+    Ok(())
+}
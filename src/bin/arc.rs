@@ -0,0 +1,22 @@
+extern crate a02;
+
+use a02::*;
+use a02::arc::*;
+
+fn main() -> std::io::Result<()> {
+    let params = ORA_SCA_Params::get();
+    println!("Total Frames: {}", params.total_frames);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let v_memory : Vec<usize> = Vec::with_capacity(params.total_frames);
+
+    let (results, targets) = exit_on_error(process_page_requests_with_target(params.total_frames, accesses, v_memory));
+
+    let targets : Vec<String> = targets.iter().map(|p| p.to_string()).collect();
+    println!("Target p over time: {}", targets.join(" "));
+    println!("Total faults: {}", get_total_faults(&results));
+
+    Ok(())
+}
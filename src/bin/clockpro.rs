@@ -0,0 +1,20 @@
+extern crate a02;
+
+use a02::*;
+use a02::clockpro::*;
+
+fn main() -> std::io::Result<()> {
+    let params = ORA_SCA_Params::get();
+    println!("Total Frames: {}", params.total_frames);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let v_memory : Vec<usize> = Vec::with_capacity(params.total_frames);
+
+    let results = exit_on_error(process_page_requests(params.total_frames, accesses, v_memory));
+
+    println!("Total faults: {}", get_total_faults(&results));
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+extern crate a02;
+
+use a02::*;
+use a02::compare::algorithm;
+use a02::cow::*;
+
+fn main() -> std::io::Result<()> {
+    let params = COW_Params::get();
+    let algorithm = exit_on_error(algorithm(&params.algorithm, params.tau));
+    println!("Algorithm: {}", algorithm.name);
+    println!("Total Frames: {}", params.total_frames);
+    println!("Memory accesses: {}", params.access_string);
+
+    let events = exit_on_error(parse_events(&params.access_string));
+    let report = exit_on_error(run(&algorithm, params.total_frames, &events));
+
+    for (event, step) in events.iter().zip(report.steps.iter()) {
+        match *event {
+            CowEvent::Fork { parent, child } => print!("Process {} forks process {}", parent, child),
+            CowEvent::Access { process, ref access } => {
+                print!("{:?}:{} by process {}", access.access_type, access.frame_number, process);
+                match step.result {
+                    Some(AccessResult::MissReplace(ref mr)) => print!(" MissReplace evicting a copy of page {}", report.copies[mr.replaced]),
+                    Some(ref result) => print!(" {:?}", result),
+                    None if step.not_mapped => print!(" not mapped, nothing to unmap"),
+                    None => print!(" still mapped by another process"),
+                }
+                if step.cow {
+                    print!(", copy on write");
+                }
+            }
+        }
+        println!(", {} shared frames", step.shared_frames);
+    }
+
+    let shared : Vec<usize> = report.steps.iter().map(|s| s.shared_frames).collect();
+    println!("Faults: {}", report.faults());
+    println!("COW faults: {}", report.cow_faults());
+    println!("Total faults: {}", report.faults() + report.cow_faults());
+    println!("Shared frames over time: {:?}", shared);
+    println!("Peak shared frames: {}", report.peak_shared_frames());
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+extern crate a02;
+
+use a02::*;
+use a02::hugepage::*;
+
+fn main() -> std::io::Result<()> {
+    let params = HUGEPAGE_Params::get();
+    println!("Total Frames: {}", params.total_frames);
+    println!("Frame size: {} KiB", params.frame_kib);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = exit_on_error(parse_sized(&params.access_string, params.frame_kib, params.total_frames));
+
+    let (results, evictions) = exit_on_error(process_page_requests_with_evictions(params.frame_kib, params.total_frames, &accesses));
+
+    for (sized, evicted) in accesses.iter().zip(evictions.iter()) {
+        if !evicted.is_empty() {
+            println!("Page {} ({} KiB) evicted {:?}", sized.access.frame_number, sized.size_kib, evicted);
+        }
+    }
+
+    println!("{:>10} {:>8} {:>8} {:>8} {:>14}", "page size", "accesses", "faults", "pages", "fragmentation");
+    for s in summarize(&accesses, &results) {
+        println!("{:>7} KiB {:>8} {:>8} {:>8} {:>13.1}%", s.size_kib, s.accesses, s.faults, s.pages, s.fragmentation() * 100.0);
+    }
+    println!("Total faults: {}", get_total_faults(&results));
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+extern crate a02;
+
+use a02::*;
+use a02::compare::algorithm;
+use a02::inverted::*;
+
+fn main() -> std::io::Result<()> {
+    let params = INVERTED_Params::get();
+    let algorithm = exit_on_error(algorithm(&params.algorithm, params.tau));
+    println!("Algorithm: {}", algorithm.name);
+    println!("Total Frames: {}", params.total_frames);
+    println!("Page table: {:?}", params.kind);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let results = exit_on_error((algorithm.run)(params.total_frames, accesses.clone()));
+    let probes = probe_counts(params.kind, &accesses, &results);
+
+    for (access, (result, probes)) in accesses.iter().zip(results.iter().zip(probes.iter())) {
+        println!("{:?}:{} {} probes {:?}", access.access_type, access.frame_number, probes, result);
+    }
+
+    let total : usize = probes.iter().sum();
+    // Frees don't look anything up
+    let lookups = results.iter().filter(|r| **r != AccessResult::Freed).count();
+    println!("Total faults: {}", get_total_faults(&results));
+    println!("Total probes: {}", total);
+    println!("Average probes: {:.2}", total as f64 / lookups.max(1) as f64);
+    println!("Most probes: {}", probes.iter().max().cloned().unwrap_or(0));
+
+    Ok(())
+}
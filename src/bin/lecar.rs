@@ -0,0 +1,24 @@
+extern crate a02;
+
+use a02::*;
+use a02::lecar::*;
+
+fn main() -> std::io::Result<()> {
+    let params = LECAR_Params::get();
+    println!("Total Frames: {}", params.total_frames);
+    println!("Seed: {}", params.seed);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let v_memory : Vec<Page> = Vec::with_capacity(params.total_frames);
+
+    let (results, steps) = exit_on_error(process_page_requests_with_weights(Learner::Lecar, params.seed, params.total_frames, accesses.clone(), v_memory));
+
+    println!("Total faults: {}", get_total_faults(&results));
+
+    std::fs::write(&params.output, weights_json(Learner::Lecar, &accesses, &results, &steps))?;
+    println!("Weights written to {}", params.output);
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+extern crate a02;
+
+use a02::*;
+use a02::lirs::*;
+
+fn main() -> std::io::Result<()> {
+    let params = LIRS_Params::get();
+    println!("Total Frames: {}", params.total_frames);
+    println!("HIR percent: {}", params.hir_percent);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let v_memory : Vec<usize> = Vec::with_capacity(params.total_frames);
+
+    let (results, evictions) = exit_on_error(process_page_requests_with_status(params.hir_percent, params.total_frames, accesses, v_memory));
+
+    for e in evictions {
+        println!("Evicted {} at {}, LIR: {:?} HIR: {:?}", e.evicted, e.time, e.lir, e.hir);
+    }
+    println!("Total faults: {}", get_total_faults(&results));
+
+    Ok(())
+}
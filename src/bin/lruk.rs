@@ -0,0 +1,26 @@
+extern crate a02;
+
+use std::collections::VecDeque;
+use a02::*;
+use a02::lruk::*;
+
+fn main() -> std::io::Result<()> {
+    let params = LRUK_Params::get();
+    println!("Total Frames: {}", params.total_frames);
+    println!("K: {}", params.k);
+    println!("Correlated reference period: {}", params.correlated_period);
+    println!("Retained histories: {}", params.retained);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let v_memory : Vec<usize> = Vec::with_capacity(params.total_frames);
+
+    let results = exit_on_error(process_page_requests(params.k, params.correlated_period, params.retained, params.total_frames, accesses.clone(), v_memory));
+    let second = exit_on_error(second::process_page_requests(params.total_frames, accesses, VecDeque::with_capacity(params.total_frames)));
+
+    println!("Total faults: {}", get_total_faults(&results));
+    println!("Second Chance faults: {}", get_total_faults(&second));
+
+    Ok(())
+}
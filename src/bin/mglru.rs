@@ -0,0 +1,39 @@
+extern crate a02;
+
+use std::collections::VecDeque;
+use a02::*;
+use a02::compare::write_backs;
+use a02::mglru::*;
+
+fn main() -> std::io::Result<()> {
+    let params = MGLRU_Params::get();
+    println!("Total Frames: {}", params.total_frames);
+    println!("Generations: {}", params.generations);
+    println!("Tau: {}", params.tau);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let frames = params.total_frames;
+    let v_memory : Vec<Page> = Vec::with_capacity(frames);
+
+    let (results, occupancy, writes) = exit_on_error(process_page_requests_with_occupancy(params.generations, frames, accesses.clone(), v_memory));
+
+    println!("Pages per generation after each access, oldest first:");
+    for (time, generations) in occupancy.iter().enumerate() {
+        let counts : Vec<String> = generations.iter().map(|c| c.to_string()).collect();
+        println!("{:>6}: {}", time, counts.join(" "));
+    }
+
+    // WSClock schedules its own writes, Second Chance doesn't know about dirty pages so we count
+    // the dirty ones it evicts
+    let (wsclock_results, wsclock_writes) = exit_on_error(wsclock::process_page_requests_with_writes(params.tau, frames, accesses.clone(), Vec::with_capacity(frames)));
+    let second_results = exit_on_error(second::process_page_requests(frames, accesses.clone(), VecDeque::with_capacity(frames)));
+
+    println!("{:<10} {:>8} {:>11}", "algorithm", "faults", "write-backs");
+    println!("{:<10} {:>8} {:>11}", "mglru", get_total_faults(&results), writes.len());
+    println!("{:<10} {:>8} {:>11}", "wsclock", get_total_faults(&wsclock_results), wsclock_writes.len());
+    println!("{:<10} {:>8} {:>11}", "second", get_total_faults(&second_results), write_backs(&accesses, &second_results));
+
+    Ok(())
+}
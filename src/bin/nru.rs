@@ -0,0 +1,22 @@
+extern crate a02;
+
+use a02::*;
+use a02::nru::*;
+
+fn main() -> std::io::Result<()> {
+    let params = NRU_Params::get();
+    println!("Total Frames: {}", params.total_frames);
+    println!("Interval: {}", params.interval);
+    println!("Seed: {}", params.seed);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let v_memory : Vec<Page> = Vec::with_capacity(params.total_frames);
+
+    let results = exit_on_error(process_page_requests(params.interval, params.seed, params.total_frames, accesses, v_memory));
+
+    println!("Total faults: {}", get_total_faults(&results));
+
+    Ok(())
+}
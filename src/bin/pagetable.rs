@@ -0,0 +1,35 @@
+extern crate a02;
+
+use a02::*;
+use a02::compare::algorithm;
+use a02::pagetable::*;
+use a02::tlb::{effective_access_time, DEFAULT_LATENCIES};
+
+fn main() -> std::io::Result<()> {
+    let params = PAGETABLE_Params::get();
+    let algorithm = exit_on_error(algorithm(&params.algorithm, params.tau));
+    let page_table = PageTable::new(params.bits.clone());
+    println!("Algorithm: {}", algorithm.name);
+    println!("Total Frames: {}", params.total_frames);
+    println!("Bits per level: {:?}", params.bits);
+    println!("TLB entries: {}", params.tlb_entries);
+    println!("Tables: {}", if params.paged { "paged" } else { "pinned" });
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    exit_on_error(page_table.check(&accesses));
+
+    let report = exit_on_error(run(&algorithm, &page_table, params.tlb_entries, params.paged, params.total_frames, &accesses));
+
+    println!("Page table pages: {}", report.table_pages);
+    println!("Page table memory: {} bytes", report.overhead_bytes);
+    println!("Walks: {}", report.walks);
+    println!("Walk memory references: {} ({} per TLB miss)", report.walk_references, page_table.levels());
+    println!("Data faults: {}", get_total_faults(&report.results));
+    println!("Page table faults: {}", report.table_faults);
+    println!("Effective access time: {:.1} ns",
+             effective_access_time(&DEFAULT_LATENCIES, page_table.levels(), &report.results, &report.tlb_accesses));
+
+    Ok(())
+}
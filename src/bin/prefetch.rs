@@ -0,0 +1,37 @@
+extern crate a02;
+
+use a02::*;
+use a02::compare::algorithm;
+use a02::prefetch::*;
+
+fn main() -> std::io::Result<()> {
+    let params = PREFETCH_Params::get();
+    let algorithm = exit_on_error(algorithm(&params.algorithm, params.tau));
+    println!("Algorithm: {}", algorithm.name);
+    println!("Total Frames: {}", params.total_frames);
+    println!("Prefetcher: {:?} up to {} pages", params.prefetcher, params.degree);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string.clone());
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let report = exit_on_error(run(&algorithm, params.prefetcher, params.degree, params.total_frames, &accesses));
+
+    for (access, (result, pages)) in accesses.iter().zip(report.results.iter().zip(report.prefetched.iter())) {
+        if pages.is_empty() {
+            println!("{:?}:{} {:?}", access.access_type, access.frame_number, result);
+        } else {
+            println!("{:?}:{} {:?}, prefetching {:?}", access.access_type, access.frame_number, result, pages);
+        }
+    }
+
+    let demand_only = exit_on_error((algorithm.run)(params.total_frames, accesses));
+    println!("Total faults: {}", get_total_faults(&report.results));
+    println!("Faults without prefetching: {}", get_total_faults(&demand_only));
+    println!("Prefetches: {} read in, {} already resident", report.issued, report.redundant);
+    println!("Prefetch hits: {}", report.useful);
+    println!("Accuracy: {:.1}%", report.accuracy() * 100.0);
+    println!("Coverage: {:.1}%", report.coverage() * 100.0);
+    println!("Pollution: {} faults on pages a prefetch pushed out", report.pollution);
+
+    Ok(())
+}
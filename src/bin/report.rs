@@ -0,0 +1,35 @@
+extern crate a02;
+
+use a02::*;
+use a02::compare::*;
+use a02::report::*;
+
+fn main() -> std::io::Result<()> {
+    let params = REPORT_Params::get();
+    println!("Total Frames: {}", params.total_frames);
+    println!("Tau: {}", params.tau);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string.clone());
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let total_frames = params.total_frames;
+    let max_frames = distinct_pages(&accesses);
+
+    let runs : Vec<AlgorithmRun> = algorithms(params.tau).iter().map(|algorithm| {
+        let results = exit_on_error((algorithm.run)(total_frames, accesses.clone()));
+        AlgorithmRun {
+            summary : Summary::new(algorithm.name, &results),
+            miss_ratio_curve : exit_on_error(miss_ratio_curve(algorithm, &accesses, max_frames)),
+            results,
+        }
+    }).collect();
+
+    let summaries : Vec<Summary> = runs.iter().map(|r| r.summary.clone()).collect();
+    print!("{}", summary_table(&summaries));
+
+    let html = render_html(&params.filename, params.total_frames, &accesses, &runs);
+    std::fs::write(&params.output, html)?;
+    println!("Report written to {}", params.output);
+
+    Ok(())
+}
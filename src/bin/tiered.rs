@@ -0,0 +1,41 @@
+extern crate a02;
+
+use a02::*;
+use a02::tiered::*;
+
+fn main() -> std::io::Result<()> {
+    let params = TIERED_Params::get();
+    for spec in &params.tiers {
+        println!("Tier {}: {} frames, {} ns, {:?}", spec.name, spec.frames, spec.latency, spec.policy);
+    }
+    println!("Promote after: {} hits", params.promote_after);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string.clone());
+    let total_frames : usize = params.tiers.iter().map(|s| s.frames).sum();
+    exit_on_error(check_pins(total_frames, &accesses));
+    let (results, tier_accesses) = exit_on_error(process_page_requests_with_tiers(&params.tiers, params.promote_after, accesses.clone()));
+
+    for (access, tier_access) in accesses.iter().zip(tier_accesses.iter()) {
+        let served = match tier_access.served {
+            Some(level) => params.tiers[level].name.as_str(),
+            None if access.access_type == AccessType::Free => "nowhere, freed",
+            None => "swap",
+        };
+        let promoted = if tier_access.promoted { " promoted" } else { "" };
+        println!("{:?}:{} from {}{}", access.access_type, access.frame_number, served, promoted);
+        for m in &tier_access.demotions {
+            let to = m.to.map_or("swap", |level| params.tiers[level].name.as_str());
+            println!("    {} demoted from {} to {}", m.page, params.tiers[m.from].name, to);
+        }
+    }
+
+    println!("{:>10} {:>8} {:>10} {:>10} {:>10} {:>10}", "tier", "hits", "promo in", "promo out", "demo in", "demo out");
+    for (spec, s) in params.tiers.iter().zip(summarize(params.tiers.len(), &tier_accesses)) {
+        println!("{:>10} {:>8} {:>10} {:>10} {:>10} {:>10}", spec.name, s.hits, s.promotions_in, s.promotions_out, s.demotions_in, s.demotions_out);
+    }
+    println!("Total faults: {}", get_total_faults(&results));
+    println!("Average latency: {:.1} ns", average_latency(&params.tiers, params.swap_latency, &results, &tier_accesses));
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+extern crate a02;
+
+use a02::*;
+use a02::compare::algorithm;
+use a02::tlb::*;
+
+fn main() -> std::io::Result<()> {
+    let params = TLB_Params::get();
+    let algorithm = exit_on_error(algorithm(&params.algorithm, params.tau));
+    println!("Algorithm: {}", algorithm.name);
+    println!("Total Frames: {}", params.total_frames);
+    println!("TLB entries: {} ({}-way, {:?})", params.entries, params.ways, params.policy);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let results = exit_on_error((algorithm.run)(params.total_frames, accesses.clone()));
+
+    let mut tlb = Tlb::new(params.entries, params.ways, params.policy);
+    let tlb_accesses = simulate(&mut tlb, &accesses, &results);
+
+    for (access, (result, tlb_access)) in accesses.iter().zip(results.iter().zip(tlb_accesses.iter())) {
+        let lookup = if *result == AccessResult::Freed {
+            "no lookup"
+        } else if tlb_access.hit {
+            "TLB hit"
+        } else {
+            "TLB miss"
+        };
+        println!("{:?}:{} {} {:?}", access.access_type, access.frame_number, lookup, result);
+        if let Some(page) = tlb_access.shootdown {
+            println!("Shootdown of page {}", page);
+        }
+    }
+
+    let tlb_hits = tlb_accesses.iter().filter(|t| t.hit).count();
+    println!("TLB hits: {}", tlb_hits);
+    let lookups = results.iter().filter(|r| **r != AccessResult::Freed).count();
+    println!("TLB misses: {}", lookups - tlb_hits);
+    println!("Shootdowns: {}", tlb_accesses.iter().filter(|t| t.shootdown.is_some()).count());
+    println!("Total faults: {}", get_total_faults(&results));
+    println!("Effective access time: {:.1} ns", effective_access_time(&DEFAULT_LATENCIES, 1, &results, &tlb_accesses));
+
+    Ok(())
+}
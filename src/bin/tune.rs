@@ -0,0 +1,24 @@
+extern crate a02;
+
+use a02::*;
+use a02::tune::*;
+
+fn main() -> std::io::Result<()> {
+    let params = TUNE_Params::get();
+    println!("Total Frames: {}", params.total_frames);
+    println!("Write cost: {}", params.write_cost);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+
+    let curve = exit_on_error(sweep_tau(params.total_frames, params.write_cost, &accesses));
+
+    print!("{}", curve_table(&curve));
+
+    if let Some(best) = best_tau(&curve) {
+        println!("Best tau: {} ({} faults, {} writes, cost {})", best.tau, best.faults, best.writes, best.cost);
+    }
+
+    Ok(())
+}
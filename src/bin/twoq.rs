@@ -0,0 +1,24 @@
+extern crate a02;
+
+use a02::*;
+use a02::twoq::*;
+
+fn main() -> std::io::Result<()> {
+    let params = ORA_SCA_Params::get();
+    println!("Total Frames: {}", params.total_frames);
+    println!("Memory accesses: {}", params.access_string);
+
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let v_memory : Vec<usize> = Vec::with_capacity(params.total_frames);
+
+    let (results, writes) = exit_on_error(process_page_requests_with_writes(params.total_frames, accesses, v_memory));
+
+    for page in &writes {
+        println!("Writing back page {}", page);
+    }
+    println!("Total faults: {}", get_total_faults(&results));
+    println!("Total writes: {}", writes.len());
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+extern crate a02;
+
+use a02::*;
+use a02::compare::algorithm;
+use a02::tlb::DEFAULT_LATENCIES;
+use a02::zswap::*;
+
+fn main() -> std::io::Result<()> {
+    let params = ZSWAP_Params::get();
+    let algorithm = exit_on_error(algorithm(&params.algorithm, params.tau));
+    println!("Algorithm: {}", algorithm.name);
+    println!("Total Frames: {}", params.total_frames);
+    println!("Pool frames: {}", params.pool_frames);
+    println!("Compression ratio: {}", params.ratio);
+    println!("Memory accesses: {}", params.access_string);
+
+    let ratios = exit_on_error(parse_ratios(&params.access_string, params.ratio));
+    let accesses = MemoryAccess::create(params.access_string);
+    exit_on_error(check_pins(params.total_frames, &accesses));
+    let results = exit_on_error((algorithm.run)(params.total_frames, accesses.clone()));
+    let pool_accesses = simulate(params.pool_frames, &accesses, &results, &ratios);
+
+    for (access, pool_access) in accesses.iter().zip(pool_accesses.iter()) {
+        print!("{:?}:{} from {:?}", access.access_type, access.frame_number, pool_access.source);
+        if !pool_access.written_back.is_empty() {
+            print!(", {:?} written to disk", pool_access.written_back);
+        }
+        println!(", {:.2} frames saved", pool_access.saved_frames);
+    }
+
+    let summary = summarize(&pool_accesses, DEFAULT_LATENCIES.fault);
+    println!("Total faults: {}", get_total_faults(&results));
+    println!("Pool hits: {}", summary.pool_hits);
+    println!("Disk reads: {}", summary.disk_reads);
+    println!("Written from the pool to disk: {}", summary.written_back);
+    println!("Frames saved: {:.2} at most, {:.2} on average", summary.peak_saved_frames, summary.average_saved_frames);
+    println!("Average fault latency: {:.1} ns", summary.average_fault_latency);
+
+    Ok(())
+}
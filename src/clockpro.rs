@@ -0,0 +1,345 @@
+// CLOCK-Pro: pages on the clock are hot or cold. Cold pages go through a test period, and one that
+// gets referenced again during it is promoted to hot. Cold pages that are evicted during their test
+// period stay on the clock as non-resident pages, so a quick comeback still counts as a reuse.
+// Three hands go around the same circular array WSClock uses:
+//   hand_cold evicts resident cold pages
+//   hand_hot demotes hot pages to cold (and ends test periods it passes)
+//   hand_test ends test periods and throws non-resident pages away
+// The target number of resident cold pages adapts: it grows when a non-resident page comes back
+// during its test and shrinks when a test period runs out without one.
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+pub fn process_page_requests(total_physical_pages : usize , accesses : Vec<MemoryAccess> , v_memory : Vec<usize>) -> Result<Vec<AccessResult>, String> {
+    let mut clock = Clock {
+        pages : Vec::new(),
+        hand_hot : 0,
+        hand_cold : 0,
+        hand_test : 0,
+        cold_target : 1,
+        total_frames : total_physical_pages,
+    };
+    let mut v_memory = v_memory;
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut pins = Pins::new();
+
+    for access in accesses.iter() {
+        pins.update(access);
+        let number = access.frame_number;
+        // A freed page comes off the clock, resident or not, the hands stay on the pages they were on
+        if access.access_type == AccessType::Free {
+            if let Some(index) = clock.find(number) {
+                clock.remove(index);
+            }
+            v_memory.retain(|&p| p != number);
+            results.push(AccessResult::Freed);
+            continue;
+        }
+        // Does the page exist and is it in memory?
+        if let Some(index) = clock.find(number) {
+            if clock.pages[index].resident {
+                clock.pages[index].referenced = true;
+                results.push(AccessResult::Hit);
+                continue;
+            }
+        }
+
+        // Crucially, here we check if we have space, if we do, it's a simple miss
+        if v_memory.len() < total_physical_pages {
+            v_memory.push(number);
+            results.push(AccessResult::MissSimple);
+        } else {
+            pins.check_evictable(total_physical_pages, number)?;
+            let evicted = clock.run_hand_cold(&pins);
+            let index = v_memory.iter().position(|&p| p == evicted).unwrap();
+            results.push(AccessResult::MissReplace(MissReplacement::new(evicted, index, number)));
+            v_memory[index] = number;
+        }
+
+        // Look it up again, the hands may have thrown its non-resident entry away meanwhile
+        match clock.find(number) {
+            Some(index) => {
+                // Back during its test period, so cold pages deserve more room and this one is hot
+                clock.cold_target = (clock.cold_target + 1).min(total_physical_pages);
+                let mut page = clock.remove(index);
+                page.hot = true;
+                page.test = false;
+                page.resident = true;
+                page.referenced = false;
+                clock.insert_at_head(page);
+            }
+            None => {
+                // Until the hot quota fills up new pages start out hot, afterwards they start cold
+                let hot = clock.hot_count() < clock.hot_target();
+                clock.insert_at_head(Page {
+                    number,
+                    hot,
+                    resident : true,
+                    test : !hot,
+                    referenced : false,
+                });
+            }
+        }
+        while clock.hot_count() > clock.hot_target() {
+            clock.run_hand_hot();
+        }
+        while clock.non_resident_count() > total_physical_pages {
+            clock.run_hand_test();
+        }
+    }
+
+    Ok(results)
+}
+
+// An entry on the clock, resident or not
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub number : usize,
+    pub hot : bool,
+    pub resident : bool,
+    // Cold pages in their test period
+    pub test : bool,
+    pub referenced : bool,
+}
+
+// The circular list and its three hands
+#[derive(Debug, Clone)]
+pub struct Clock {
+    pub pages : Vec<Page>,
+    pub hand_hot : usize,
+    pub hand_cold : usize,
+    pub hand_test : usize,
+    // How many resident cold pages we'd like to have
+    pub cold_target : usize,
+    pub total_frames : usize,
+}
+
+impl Clock {
+    fn find(&self , number : usize) -> Option<usize> {
+        self.pages.iter().position(|p| p.number == number)
+    }
+
+    fn hot_target(&self) -> usize {
+        self.total_frames - self.cold_target
+    }
+
+    fn hot_count(&self) -> usize {
+        self.pages.iter().filter(|p| p.hot).count()
+    }
+
+    fn non_resident_count(&self) -> usize {
+        self.pages.iter().filter(|p| !p.resident).count()
+    }
+
+    // The list head sits just behind hand_hot, so the hot hand gets to it last
+    fn insert_at_head(&mut self , page : Page) {
+        let length = self.pages.len().max(1);
+        let index = self.hand_hot % length;
+        self.pages.insert(index, page);
+        for hand in [&mut self.hand_hot, &mut self.hand_cold, &mut self.hand_test] {
+            *hand %= length;
+            if *hand >= index {
+                *hand += 1;
+            }
+        }
+    }
+
+    // Hands past the removed entry step back one so they keep pointing at the same page, a hand
+    // right on it ends up on the next one
+    fn remove(&mut self , index : usize) -> Page {
+        let length = self.pages.len();
+        let page = self.pages.remove(index);
+        for hand in [&mut self.hand_hot, &mut self.hand_cold, &mut self.hand_test] {
+            *hand %= length;
+            if *hand > index {
+                *hand -= 1;
+            }
+            *hand %= self.pages.len().max(1);
+        }
+        page
+    }
+
+    // Goes around until a resident cold page can be evicted and gives back its number. Pinned
+    // pages are passed over, and if a whole round finds nothing else a hot page is demoted so there
+    // are more cold pages to look at.
+    fn run_hand_cold(&mut self , pins : &Pins) -> usize {
+        let mut passed = 0;
+        loop {
+            if passed > self.pages.len() && self.hot_count() > 0 {
+                self.run_hand_hot();
+                passed = 0;
+            }
+            let index = self.hand_cold % self.pages.len();
+            let page = self.pages[index].clone();
+            if page.hot || !page.resident || pins.contains(page.number) {
+                self.hand_cold = index + 1;
+                passed += 1;
+            } else if page.referenced {
+                // Referenced during its test, it's hot now. Otherwise it gets a fresh test period.
+                // Either way it moves to the head and the hand is already on the next page.
+                let mut page = self.remove(index);
+                page.referenced = false;
+                if page.test {
+                    page.hot = true;
+                    page.test = false;
+                } else {
+                    page.test = true;
+                }
+                self.insert_at_head(page);
+                while self.hot_count() > self.hot_target() {
+                    self.run_hand_hot();
+                }
+            } else if page.test {
+                // Evicted, but it stays around as a non-resident page until its test is over
+                self.pages[index].resident = false;
+                self.hand_cold = index + 1;
+                return page.number;
+            } else {
+                self.remove(index);
+                return page.number;
+            }
+        }
+    }
+
+    // Goes around until a hot page has been demoted
+    fn run_hand_hot(&mut self) {
+        loop {
+            let index = self.hand_hot % self.pages.len();
+            let page = self.pages[index].clone();
+            if page.hot {
+                self.hand_hot = index + 1;
+                if page.referenced {
+                    self.pages[index].referenced = false;
+                } else {
+                    self.pages[index].hot = false;
+                    return;
+                }
+            } else if page.test && self.end_test(index) {
+                // Thrown away, the hand is already on the next page
+            } else {
+                self.hand_hot = index + 1;
+            }
+        }
+    }
+
+    // Goes around until a non-resident page has been thrown away
+    fn run_hand_test(&mut self) {
+        loop {
+            let index = self.hand_test % self.pages.len();
+            let page = self.pages[index].clone();
+            if !page.hot && page.test && self.end_test(index) {
+                return;
+            }
+            self.hand_test = index + 1;
+        }
+    }
+
+    // The test period ran out without a comeback, so cold pages get a bit less room. A
+    // non-resident page has no reason to stay on the clock anymore, true if it was thrown away.
+    fn end_test(&mut self , index : usize) -> bool {
+        self.pages[index].test = false;
+        if self.pages[index].resident {
+            false
+        } else {
+            self.remove(index);
+            self.cold_target = self.cold_target.saturating_sub(1).max(1);
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {get_total_faults, wsclock};
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 W:1 R:1 W:1"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        for result in results.iter().skip(1) {
+            assert_eq!(*result , AccessResult::Hit);
+        }
+        assert_eq!(get_total_faults(&results) , 1);
+    }
+
+    #[test]
+    fn new_cold_page_is_evicted_before_hot_ones() {
+        // With 3 frames and a cold target of 1, 1 and 2 start hot and 3 is cold
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4"));
+        let total_pages = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+
+        // Replaced R:3 at index 2 with R:4
+        let mr = MissReplacement::new(3, 2, 4);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+    }
+
+    #[test]
+    fn non_resident_page_coming_back_is_promoted() {
+        // 3 is evicted during its test period and comes back, it should now be hot and survive
+        // while 5 and 6 stream through the single cold frame
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:3 R:5 R:6 R:3"));
+        let total_pages = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_ne!(results[4] , AccessResult::Hit);
+        assert_eq!(results[7] , AccessResult::Hit);
+    }
+
+    #[test]
+    fn keeps_hot_pages_through_a_scan_unlike_wsclock() {
+        let mut trace = String::from("R:1 R:2 R:1 R:2 R:1 R:2");
+        for page in 10..30 {
+            trace.push_str(&format!(" R:{}", page));
+        }
+        trace.push_str(" R:1 R:2");
+        let accesses = MemoryAccess::create(trace);
+
+        let results = process_page_requests(3, accesses.clone(), Vec::with_capacity(3)).unwrap();
+        assert_eq!(results[26] , AccessResult::Hit);
+        assert_eq!(results[27] , AccessResult::Hit);
+
+        let results = wsclock::process_page_requests(5, 3, accesses, Vec::with_capacity(3)).unwrap();
+        assert_ne!(results[26] , AccessResult::Hit);
+        assert_ne!(results[27] , AccessResult::Hit);
+    }
+
+    #[test]
+    fn hands_stay_on_their_pages_around_inserts_and_removes() {
+        let page = |number| Page { number, hot : false, resident : true, test : false, referenced : false };
+        let mut clock = Clock {
+            pages : vec![page(1), page(2), page(3)],
+            hand_hot : 1,
+            hand_cold : 2,
+            hand_test : 0,
+            cold_target : 1,
+            total_frames : 3,
+        };
+        clock.insert_at_head(page(4));
+        assert_eq!(clock.pages.iter().map(|p| p.number).collect::<Vec<_>>() , vec![1, 4, 2, 3]);
+        assert_eq!(clock.pages[clock.hand_hot].number , 2);
+        assert_eq!(clock.pages[clock.hand_cold].number , 3);
+        assert_eq!(clock.pages[clock.hand_test].number , 1);
+
+        clock.remove(0);
+        assert_eq!(clock.pages[clock.hand_hot].number , 2);
+        assert_eq!(clock.pages[clock.hand_cold].number , 3);
+        assert_eq!(clock.pages[clock.hand_test].number , 4);
+    }
+}
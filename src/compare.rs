@@ -0,0 +1,424 @@
+// Runs every algorithm over the same trace so their AccessResult vectors can be compared
+use std::collections::{HashSet, VecDeque};
+use frequency::Frequency;
+use lecar::Learner;
+use {aging, arc, clockpro, frequency, gclock, lecar, linux, lirs, lruk, mglru, nru, optimal, s3fifo, second, sieve, twoq, wsclock};
+use {check_pins, AccessResult, AccessType, MemoryAccess};
+
+// A named algorithm that takes a frame count and a trace and gives back its results, or an error
+// when the trace pins every frame
+pub struct Algorithm {
+    pub name : &'static str,
+    pub run : Run,
+}
+
+pub type Run = Box<dyn Fn(usize, Vec<MemoryAccess>) -> Result<Vec<AccessResult>, String>>;
+
+// Every algorithm we know about, tau only matters to WSClock and the rest run with their defaults
+pub fn algorithms(tau : usize) -> Vec<Algorithm> {
+    vec![
+        Algorithm {
+            name : "optimal",
+            run : Box::new(|frames, accesses| {
+                optimal::process_page_requests(frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "second",
+            run : Box::new(|frames, accesses| {
+                second::process_page_requests(frames, accesses, VecDeque::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "wsclock",
+            run : Box::new(move |frames, accesses| {
+                wsclock::process_page_requests(tau, frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "aging",
+            run : Box::new(|frames, accesses| {
+                aging::process_page_requests(aging::DEFAULT_TICK, aging::DEFAULT_BITS, frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "nru",
+            run : Box::new(|frames, accesses| {
+                nru::process_page_requests(nru::DEFAULT_INTERVAL, nru::DEFAULT_SEED, frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "lfu",
+            run : Box::new(|frames, accesses| {
+                frequency::process_page_requests(Frequency::Least, frequency::DEFAULT_DECAY, frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "mfu",
+            run : Box::new(|frames, accesses| {
+                frequency::process_page_requests(Frequency::Most, frequency::DEFAULT_DECAY, frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "arc",
+            run : Box::new(|frames, accesses| {
+                arc::process_page_requests(frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "clockpro",
+            run : Box::new(|frames, accesses| {
+                clockpro::process_page_requests(frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "lirs",
+            run : Box::new(|frames, accesses| {
+                lirs::process_page_requests(lirs::DEFAULT_HIR_PERCENT, frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "twoq",
+            run : Box::new(|frames, accesses| {
+                twoq::process_page_requests(frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "linux",
+            run : Box::new(|frames, accesses| {
+                linux::process_page_requests(frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "mglru",
+            run : Box::new(|frames, accesses| {
+                mglru::process_page_requests(mglru::DEFAULT_GENERATIONS, frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "lruk",
+            run : Box::new(|frames, accesses| {
+                lruk::process_page_requests(lruk::DEFAULT_K, lruk::DEFAULT_CORRELATED_PERIOD, lruk::DEFAULT_RETAINED, frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "gclock",
+            run : Box::new(|frames, accesses| {
+                gclock::process_page_requests(gclock::DEFAULT_MAX_COUNT, gclock::DEFAULT_INITIAL_COUNT, frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "lecar",
+            run : Box::new(|frames, accesses| {
+                lecar::process_page_requests(Learner::Lecar, lecar::DEFAULT_SEED, frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "cacheus",
+            run : Box::new(|frames, accesses| {
+                lecar::process_page_requests(Learner::Cacheus, lecar::DEFAULT_SEED, frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "sieve",
+            run : Box::new(|frames, accesses| {
+                sieve::process_page_requests(frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+        Algorithm {
+            name : "s3fifo",
+            run : Box::new(|frames, accesses| {
+                s3fifo::process_page_requests(frames, accesses, Vec::with_capacity(frames))
+            }),
+        },
+    ]
+}
+
+// Looks an algorithm up by the name it has in the list above
+pub fn algorithm(name : &str , tau : usize) -> Result<Algorithm, String> {
+    let mut all = algorithms(tau);
+    match all.iter().position(|a| a.name == name) {
+        Some(i) => Ok(all.swap_remove(i)),
+        None => {
+            let names : Vec<&str> = all.iter().map(|a| a.name).collect();
+            Err(format!("Unknown algorithm {}, pick one of {}", name, names.join(", ")))
+        }
+    }
+}
+
+// Counts of each kind of result for a single run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub name : String,
+    pub accesses : usize,
+    pub hits : usize,
+    pub simple_misses : usize,
+    pub replacements : usize,
+}
+
+impl Summary {
+    // Constructor tallying the results up
+    pub fn new(name : &str , results : &[AccessResult]) -> Summary {
+        let mut summary = Summary {
+            name : String::from(name),
+            // Frees aren't accesses
+            accesses : results.iter().filter(|r| **r != AccessResult::Freed).count(),
+            hits : 0,
+            simple_misses : 0,
+            replacements : 0,
+        };
+        for result in results {
+            match *result {
+                AccessResult::Hit | AccessResult::PrefetchHit => summary.hits += 1,
+                AccessResult::Freed => (),
+                AccessResult::MissSimple => summary.simple_misses += 1,
+                AccessResult::MissReplace(_) => summary.replacements += 1,
+            }
+        }
+        summary
+    }
+
+    pub fn faults(&self) -> usize {
+        self.simple_misses + self.replacements
+    }
+
+    pub fn miss_ratio(&self) -> f64 {
+        if self.accesses == 0 {
+            0.0
+        } else {
+            self.faults() as f64 / self.accesses as f64
+        }
+    }
+}
+
+// Runs the algorithm once per frame count from 1 up to max_frames and collects the miss ratios.
+// Frame counts that the trace pins too many pages for can't be run and are None, and a trace that
+// pins too many for max_frames too is an error.
+pub fn miss_ratio_curve(algorithm : &Algorithm , accesses : &[MemoryAccess] , max_frames : usize) -> Result<Vec<Option<f64>>, String> {
+    check_pins(max_frames, accesses)?;
+    (1..=max_frames)
+        .map(|frames| {
+            if check_pins(frames, accesses).is_err() {
+                return Ok(None);
+            }
+            let results = (algorithm.run)(frames, accesses.to_vec())?;
+            Ok(Some(Summary::new(algorithm.name, &results).miss_ratio()))
+        })
+        .collect()
+}
+
+// Number of different pages in the trace, past this many frames every algorithm only has cold misses
+pub fn distinct_pages(accesses : &[MemoryAccess]) -> usize {
+    let mut pages : Vec<usize> = accesses.iter()
+        .filter(|a| a.access_type != AccessType::Free)
+        .map(|a| a.frame_number)
+        .collect();
+    pages.sort();
+    pages.dedup();
+    pages.len()
+}
+
+// How many evicted pages had been written to since they were loaded, for algorithms that don't
+// keep track of their own write-backs. Every one of those costs a write to disk, a freed page is
+// thrown away without one.
+pub fn write_backs(accesses : &[MemoryAccess] , results : &[AccessResult]) -> usize {
+    let mut dirty : HashSet<usize> = HashSet::new();
+    let mut writes = 0;
+    for (access, result) in accesses.iter().zip(results.iter()) {
+        if let AccessResult::MissReplace(ref mr) = *result {
+            if dirty.remove(&mr.replaced) {
+                writes += 1;
+            }
+        }
+        match access.access_type {
+            AccessType::Write => { dirty.insert(access.frame_number); }
+            AccessType::Free => { dirty.remove(&access.frame_number); }
+            _ => (),
+        }
+    }
+    writes
+}
+
+// Plain text version of the summaries, one algorithm per row
+pub fn summary_table(summaries : &[Summary]) -> String {
+    let mut table = format!("{:<12} {:>8} {:>8} {:>8} {:>8} {:>8} {:>10}\n",
+                            "algorithm", "accesses", "hits", "simple", "replace", "faults", "miss ratio");
+    for s in summaries {
+        table.push_str(&format!("{:<12} {:>8} {:>8} {:>8} {:>8} {:>8} {:>10.3}\n",
+                                s.name, s.accesses, s.hits, s.simple_misses, s.replacements,
+                                s.faults(), s.miss_ratio()));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use random::Random;
+    use {check_pins, get_total_faults, MissReplacement};
+
+    #[test]
+    fn summary_counts_every_kind_of_result() {
+        let results = vec![
+            AccessResult::MissSimple,
+            AccessResult::Hit,
+            AccessResult::MissReplace(MissReplacement::new(1, 0, 2)),
+            AccessResult::Freed,
+            AccessResult::Hit,
+        ];
+        let summary = Summary::new("test", &results);
+        // The free isn't an access
+        assert_eq!(summary.accesses , 4);
+        assert_eq!(summary.hits , 2);
+        assert_eq!(summary.simple_misses , 1);
+        assert_eq!(summary.replacements , 1);
+        assert_eq!(summary.faults() , get_total_faults(&results));
+        assert_eq!(summary.miss_ratio() , 0.5);
+    }
+
+    #[test]
+    fn write_backs_count_dirty_pages_that_get_evicted() {
+        // 1 is written and evicted, 2 is written but never leaves, 3 goes clean
+        let accesses = MemoryAccess::create(String::from("W:1 W:2 R:3 R:1"));
+        let results = vec![
+            AccessResult::MissSimple,
+            AccessResult::MissSimple,
+            AccessResult::MissReplace(MissReplacement::new(1, 0, 3)),
+            AccessResult::MissReplace(MissReplacement::new(3, 0, 1)),
+        ];
+        assert_eq!(write_backs(&accesses, &results) , 1);
+    }
+
+    #[test]
+    fn every_algorithm_runs_over_the_whole_trace() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:2 R:1"));
+        for algorithm in algorithms(5) {
+            let results = (algorithm.run)(3, accesses.clone()).unwrap();
+            assert_eq!(results.len() , 6);
+            assert_eq!(results[0] , AccessResult::MissSimple);
+        }
+        let results = (algorithms(5)[1].run)(3, accesses).unwrap();
+        assert_eq!(results[5] , AccessResult::MissReplace(MissReplacement::new(3, 0, 1)));
+    }
+
+    #[test]
+    fn algorithms_can_be_looked_up_by_name() {
+        assert_eq!(algorithm("second", 5).unwrap().name , "second");
+        let error = algorithm("belady", 5).err().unwrap();
+        assert!(error.starts_with("Unknown algorithm belady, pick one of optimal, second, wsclock"));
+    }
+
+    #[test]
+    fn miss_ratio_curve_bottoms_out_at_cold_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1 R:2 R:3"));
+        let max_frames = distinct_pages(&accesses);
+        assert_eq!(max_frames , 3);
+        for algorithm in algorithms(5) {
+            let curve = miss_ratio_curve(&algorithm, &accesses, max_frames).unwrap();
+            assert_eq!(curve.len() , 3);
+            assert_eq!(curve[2] , Some(0.5));
+        }
+    }
+
+    #[test]
+    fn miss_ratio_curve_skips_frame_counts_the_pins_fill_up() {
+        let accesses = MemoryAccess::create(String::from("L:1 L:2 R:3 R:1 R:2 R:3"));
+        for algorithm in algorithms(5) {
+            let curve = miss_ratio_curve(&algorithm, &accesses, 3).unwrap();
+            assert_eq!(curve[0] , None);
+            assert_eq!(curve[1] , None);
+            assert_eq!(curve[2] , Some(0.5));
+            assert!(miss_ratio_curve(&algorithm, &accesses, 2).is_err());
+        }
+    }
+
+    #[test]
+    fn every_algorithm_is_consistent_with_a_replay_of_its_results() {
+        let mut random = Random::new(1);
+        for frames in 1..6 {
+            let trace : Vec<String> = (0..300).map(|_| {
+                let page = random.below(12);
+                match random.below(12) {
+                    0..=2 => format!("W:{}", page),
+                    3 => format!("F:{}", page),
+                    4 => format!("F:{}-{}", page, page + random.below(3)),
+                    _ => format!("R:{}", page),
+                }
+            }).collect();
+            let accesses = MemoryAccess::create(trace.join(" "));
+            for algorithm in algorithms(3) {
+                let results = (algorithm.run)(frames, accesses.clone()).unwrap();
+                assert_eq!(results.len() , accesses.len(), "{}", algorithm.name);
+                let mut resident : HashSet<usize> = HashSet::new();
+                for (access, result) in accesses.iter().zip(results.iter()) {
+                    let page = access.frame_number;
+                    match *result {
+                        AccessResult::Hit | AccessResult::PrefetchHit => assert!(resident.contains(&page), "{}", algorithm.name),
+                        AccessResult::Freed => {
+                            assert_eq!(access.access_type , AccessType::Free, "{}", algorithm.name);
+                            resident.remove(&page);
+                        }
+                        // Frames that were freed have to be filled before anything is evicted
+                        AccessResult::MissSimple => {
+                            assert!(resident.len() < frames, "{}", algorithm.name);
+                            assert!(resident.insert(page), "{}", algorithm.name);
+                        }
+                        AccessResult::MissReplace(ref mr) => {
+                            assert_eq!(resident.len() , frames, "{}", algorithm.name);
+                            assert_eq!(mr.new_page , page, "{}", algorithm.name);
+                            assert!(resident.remove(&mr.replaced), "{}", algorithm.name);
+                            assert!(resident.insert(page), "{}", algorithm.name);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pinned_pages_are_never_evicted() {
+        let mut random = Random::new(2);
+        for frames in 2..7 {
+            // Never more than frames - 1 pinned at once, so every fault still has a victim
+            let mut pinned : Vec<usize> = Vec::new();
+            let trace : Vec<String> = (0..300).map(|_| {
+                let page = random.below(12);
+                match random.below(10) {
+                    0 if pinned.len() + 1 < frames && !pinned.contains(&page) => {
+                        pinned.push(page);
+                        format!("L:{}", page)
+                    }
+                    1 if !pinned.is_empty() => format!("U:{}", pinned.remove(random.below(pinned.len()))),
+                    2 => format!("W:{}", page),
+                    _ => format!("R:{}", page),
+                }
+            }).collect();
+            let accesses = MemoryAccess::create(trace.join(" "));
+            assert!(check_pins(frames, &accesses).is_ok());
+            for algorithm in algorithms(3) {
+                let results = (algorithm.run)(frames, accesses.clone()).unwrap();
+                let mut pins : HashSet<usize> = HashSet::new();
+                for (access, result) in accesses.iter().zip(results.iter()) {
+                    match access.access_type {
+                        AccessType::Lock => { pins.insert(access.frame_number); }
+                        AccessType::Unlock | AccessType::Free => { pins.remove(&access.frame_number); }
+                        _ => (),
+                    }
+                    if let AccessResult::MissReplace(ref mr) = *result {
+                        assert!(!pins.contains(&mr.replaced), "{} evicted pinned page {}", algorithm.name, mr.replaced);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pinning_every_frame_is_caught_up_front() {
+        let accesses = MemoryAccess::create(String::from("L:1 L:2 R:1 R:3"));
+        assert_eq!(check_pins(2, &accesses) , Err(String::from("All 2 frames are pinned when page 3 faults at access 3")));
+        assert!(check_pins(3, &accesses).is_ok());
+        // Unlocking one makes room again
+        let accesses = MemoryAccess::create(String::from("L:1 L:2 U:2 R:3"));
+        assert!(check_pins(2, &accesses).is_ok());
+    }
+}
@@ -0,0 +1,339 @@
+// Fork and copy-on-write. The trace has several processes in it, R:7:p=2 is process 2 reading its
+// page 7 and plain R:7 is process 0, and K:0:2 is process 0 forking process 2. A fork doesn't copy
+// anything, the child maps every page the parent has to the same copy, so a frame can be mapped by
+// several processes at once. Reading a shared page is like reading any other, but writing one
+// is a COW fault: the writer gets a private copy of its own, which needs a frame like any new page,
+// and the other processes keep the old one. F:7:p=2 unmaps page 7 from process 2, and the copy is
+// only freed once nobody maps it anymore. Unmapping a page the process doesn't have mapped does
+// nothing at all. L:7:p=2 and U:7:p=2 lock and unlock whatever copy process 2 maps page 7 to
+// right then, so a lock stays with that copy even after a COW fault moves the process off of it.
+// Every copy is a page of its own to the replacement algorithm underneath, so this works with any
+// of them: the trace is turned into accesses to copies, run, and the results are matched back up.
+use std::collections::{HashMap, HashSet};
+use compare::Algorithm;
+use {access_field, exit_on_error, parse_file, parse_number};
+use {AccessResult, AccessType, MemoryAccess};
+
+// Fork and copy-on-write parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct COW_Params {
+    pub algorithm : String,
+    pub total_frames : usize,
+    pub tau : usize,
+    pub access_string : String,
+}
+
+impl COW_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> COW_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let algorithm = exit_on_error(args.get(1).cloned().ok_or_else(|| String::from("No algorithm provided")));
+        let frames = exit_on_error(parse_number("frame count", args.get(2)));
+        let tau = exit_on_error(parse_number("tau", args.get(3)));
+        let file = exit_on_error(parse_file(args.get(4)));
+        COW_Params {
+            algorithm,
+            total_frames : frames,
+            tau,
+            access_string : file,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CowEvent {
+    // A read, write, lock, unlock or free of one of the process's pages
+    Access { process : usize , access : MemoryAccess },
+    Fork { parent : usize , child : usize },
+}
+
+// Like MemoryAccess::create, but keeps the process of every access and the forks. A child has to
+// be a process that hasn't shown up yet.
+pub fn parse_events(input_string : &str) -> Result<Vec<CowEvent>, String> {
+    let mut seen : HashSet<usize> = HashSet::new();
+    seen.insert(0);
+    let mut events : Vec<CowEvent> = Vec::new();
+    for token in input_string.split_whitespace() {
+        let parts : Vec<&str> = token.split(':').collect();
+        let invalid = || format!("Invalid memory access token: {}", token);
+        let number = |i : usize| parts.get(i).ok_or_else(invalid)?.parse::<usize>().map_err(|_| invalid());
+        if parts[0] == "K" {
+            let (parent, child) = (number(1)?, number(2)?);
+            if !seen.insert(child) {
+                return Err(format!("Process {} forks process {}, which already exists", parent, child));
+            }
+            seen.insert(parent);
+            events.push(CowEvent::Fork { parent, child });
+            continue;
+        }
+        let access_type = match parts[0] {
+            "R" => AccessType::Read,
+            "W" => AccessType::Write,
+            "L" => AccessType::Lock,
+            "U" => AccessType::Unlock,
+            "F" => AccessType::Free,
+            _ => return Err(invalid()),
+        };
+        let process = match access_field(token, "p")? {
+            Some(p) => p.parse::<usize>().map_err(|_| invalid())?,
+            None => 0,
+        };
+        seen.insert(process);
+        let pages = parts.get(1).ok_or_else(invalid)?;
+        let (first, last) = match pages.split_once('-') {
+            Some((first, last)) if access_type == AccessType::Free => {
+                (first.parse::<usize>().map_err(|_| invalid())?, last.parse::<usize>().map_err(|_| invalid())?)
+            }
+            _ => (number(1)?, number(1)?),
+        };
+        for frame_number in first..=last {
+            events.push(CowEvent::Access { process, access : MemoryAccess { frame_number, access_type : access_type.clone() } });
+        }
+    }
+    Ok(events)
+}
+
+// What one event did
+#[derive(Debug, Clone, PartialEq)]
+pub struct CowStep {
+    // The algorithm's result, None for a fork or an unmap that leaves the copy to other processes
+    pub result : Option<AccessResult>,
+    // A write to a shared page that had to make a private copy
+    pub cow : bool,
+    // An unmap of a page the process didn't have mapped, its result is None too
+    pub not_mapped : bool,
+    // Resident frames mapped by more than one process right after it
+    pub shared_frames : usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct CowReport {
+    pub steps : Vec<CowStep>,
+    // What the algorithm ran over, one page number per copy
+    pub trace : Vec<MemoryAccess>,
+    // Copy -> the page it's a copy of
+    pub copies : Vec<usize>,
+}
+
+impl CowReport {
+    // Faults that would have happened without any sharing, leaving out the COW faults
+    pub fn faults(&self) -> usize {
+        self.steps.iter().filter(|s| !s.cow && s.result.as_ref().is_some_and(|r| r.is_fault())).count()
+    }
+
+    pub fn cow_faults(&self) -> usize {
+        self.steps.iter().filter(|s| s.cow).count()
+    }
+
+    pub fn peak_shared_frames(&self) -> usize {
+        self.steps.iter().map(|s| s.shared_frames).max().unwrap_or(0)
+    }
+}
+
+// One event turned into an access to a copy
+struct Translated {
+    // Where its access is in the trace, if it has one
+    index : Option<usize>,
+    cow : bool,
+    not_mapped : bool,
+    // Copies whose number of mappings changed, and what it changed to
+    changes : Vec<(usize, usize)>,
+}
+
+// Turns the events into accesses to copies, gives back the trace, what every copy is a copy of and
+// what became of every event
+fn translate(events : &[CowEvent]) -> (Vec<MemoryAccess>, Vec<usize>, Vec<Translated>) {
+    let mut trace : Vec<MemoryAccess> = Vec::new();
+    let mut copies : Vec<usize> = Vec::new();
+    let mut mappings : Vec<usize> = Vec::new();
+    // (process, page) -> copy
+    let mut maps : HashMap<(usize, usize), usize> = HashMap::new();
+    let mut translated = Vec::with_capacity(events.len());
+    for event in events {
+        let mut changes : Vec<(usize, usize)> = Vec::new();
+        match *event {
+            CowEvent::Fork { parent, child } => {
+                let inherited : Vec<(usize, usize)> = maps.iter()
+                    .filter(|&(&(process, _), _)| process == parent)
+                    .map(|(&(_, page), &copy)| (page, copy))
+                    .collect();
+                for (page, copy) in inherited {
+                    maps.insert((child, page), copy);
+                    mappings[copy] += 1;
+                    changes.push((copy, mappings[copy]));
+                }
+                translated.push(Translated { index : None, cow : false, not_mapped : false, changes });
+            }
+            CowEvent::Access { process, ref access } => {
+                let page = access.frame_number;
+                let key = (process, page);
+                if access.access_type == AccessType::Free {
+                    let copy = match maps.remove(&key) {
+                        Some(copy) => copy,
+                        None => {
+                            translated.push(Translated { index : None, cow : false, not_mapped : true, changes });
+                            continue;
+                        }
+                    };
+                    mappings[copy] -= 1;
+                    changes.push((copy, mappings[copy]));
+                    let index = if mappings[copy] > 0 {
+                        None
+                    } else {
+                        trace.push(MemoryAccess { frame_number : copy, access_type : AccessType::Free });
+                        Some(trace.len() - 1)
+                    };
+                    translated.push(Translated { index, cow : false, not_mapped : false, changes });
+                    continue;
+                }
+                let shared = maps.get(&key).is_some_and(|&copy| mappings[copy] > 1);
+                let cow = shared && access.access_type == AccessType::Write;
+                if cow {
+                    let old = maps[&key];
+                    mappings[old] -= 1;
+                    changes.push((old, mappings[old]));
+                }
+                if cow || !maps.contains_key(&key) {
+                    // A brand new copy, the algorithm has never seen its page number
+                    copies.push(page);
+                    mappings.push(1);
+                    maps.insert(key, copies.len() - 1);
+                    changes.push((copies.len() - 1, 1));
+                }
+                trace.push(MemoryAccess { frame_number : maps[&key], access_type : access.access_type.clone() });
+                translated.push(Translated { index : Some(trace.len() - 1), cow, not_mapped : false, changes });
+            }
+        }
+    }
+    (trace, copies, translated)
+}
+
+pub fn run(algorithm : &Algorithm , total_frames : usize , events : &[CowEvent]) -> Result<CowReport, String> {
+    let (trace, copies, translated) = translate(events);
+    let results = (algorithm.run)(total_frames, trace.clone())?;
+
+    let mut resident : HashSet<usize> = HashSet::new();
+    let mut mappings : Vec<usize> = vec![0; copies.len()];
+    let steps = translated.into_iter().map(|Translated { index, cow, not_mapped, changes }| {
+        for (copy, count) in changes {
+            mappings[copy] = count;
+        }
+        let result = index.map(|i| results[i].clone());
+        match (index, &result) {
+            (Some(i), Some(AccessResult::MissSimple)) => { resident.insert(trace[i].frame_number); }
+            (Some(i), Some(AccessResult::MissReplace(mr))) => {
+                resident.remove(&mr.replaced);
+                resident.insert(trace[i].frame_number);
+            }
+            (Some(i), Some(AccessResult::Freed)) => { resident.remove(&trace[i].frame_number); }
+            _ => (),
+        }
+        let shared_frames = resident.iter().filter(|&&copy| mappings[copy] > 1).count();
+        CowStep { result, cow, not_mapped, shared_frames }
+    }).collect();
+
+    Ok(CowReport { steps, trace, copies })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compare::{algorithm, algorithms};
+    use {get_total_faults, MissReplacement};
+
+    fn events(s : &str) -> Vec<CowEvent> {
+        parse_events(s).unwrap()
+    }
+
+    #[test]
+    fn events_keep_their_process_and_forks() {
+        let parsed = events("R:1 K:0:2 W:1:p=2 F:3-4:p=2");
+        let access = |process, frame_number, access_type| CowEvent::Access { process, access : MemoryAccess { frame_number, access_type } };
+        assert_eq!(parsed , vec![
+            access(0, 1, AccessType::Read),
+            CowEvent::Fork { parent : 0, child : 2 },
+            access(2, 1, AccessType::Write),
+            access(2, 3, AccessType::Free),
+            access(2, 4, AccessType::Free),
+        ]);
+        assert_eq!(events("U:5:p=3")[0] , access(3, 5, AccessType::Unlock));
+        assert!(parse_events("R:1:p=1 K:0:1").is_err());
+        assert!(parse_events("K:0").is_err());
+        assert!(parse_events("X:1").is_err());
+        assert!(parse_events("R:1:2").is_err());
+    }
+
+    #[test]
+    fn write_to_a_shared_page_is_a_cow_fault() {
+        // After the fork 1 and 2 are shared, the child reads 1 from the same frame, then writes it
+        // and gets a copy of its own. The parent's 1 isn't shared anymore so writing it is a hit.
+        let report = run(&algorithm("second", 0).unwrap(), 4, &events("R:1 R:2 K:0:1 R:1:p=1 W:1:p=1 W:1")).unwrap();
+        let results : Vec<Option<AccessResult>> = report.steps.iter().map(|s| s.result.clone()).collect();
+        assert_eq!(results , vec![
+            Some(AccessResult::MissSimple),
+            Some(AccessResult::MissSimple),
+            None,
+            Some(AccessResult::Hit),
+            Some(AccessResult::MissSimple),
+            Some(AccessResult::Hit),
+        ]);
+        let cows : Vec<bool> = report.steps.iter().map(|s| s.cow).collect();
+        assert_eq!(cows , vec![false, false, false, false, true, false]);
+        let shared : Vec<usize> = report.steps.iter().map(|s| s.shared_frames).collect();
+        assert_eq!(shared , vec![0, 0, 2, 2, 1, 1]);
+        assert_eq!(report.faults() , 2);
+        assert_eq!(report.cow_faults() , 1);
+        assert_eq!(report.peak_shared_frames() , 2);
+        assert_eq!(report.copies , vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn shared_copy_is_freed_by_the_last_process_to_unmap_it() {
+        // The parent's unmap leaves 1 to the child, the child's frees the frame, and 3 and 4 both
+        // find a free frame
+        let report = run(&algorithm("second", 0).unwrap(), 2, &events("R:1 K:0:1 F:1 F:1:p=1 R:3 R:4")).unwrap();
+        assert_eq!(report.steps[2] , CowStep { result : None, cow : false, not_mapped : false, shared_frames : 0 });
+        assert_eq!(report.steps[3].result , Some(AccessResult::Freed));
+        assert_eq!(report.steps[4].result , Some(AccessResult::MissSimple));
+        assert_eq!(report.steps[5].result , Some(AccessResult::MissSimple));
+    }
+
+    #[test]
+    fn unmapping_a_page_that_isnt_mapped_does_nothing() {
+        // Process 1 never had 2, and the parent's 1 is still mapped by the child after it's gone
+        let report = run(&algorithm("second", 0).unwrap(), 2, &events("R:1 K:0:1 F:2:p=1 F:1 R:1:p=1")).unwrap();
+        assert_eq!(report.steps[2] , CowStep { result : None, cow : false, not_mapped : true, shared_frames : 1 });
+        assert_eq!(report.steps[3] , CowStep { result : None, cow : false, not_mapped : false, shared_frames : 0 });
+        assert_eq!(report.steps[4].result , Some(AccessResult::Hit));
+        assert_eq!(report.trace.len() , 2);
+    }
+
+    #[test]
+    fn lock_stays_with_the_copy_it_was_taken_on() {
+        // The parent locks 1 while it's shared, the child's write moves it to a copy of its own, so
+        // the shared copy is the one that can't be evicted and 2 and 3 take the child's frame
+        let report = run(&algorithm("second", 0).unwrap(), 2, &events("R:1 K:0:1 L:1 W:1:p=1 R:2 R:3")).unwrap();
+        let results : Vec<Option<AccessResult>> = report.steps.iter().map(|s| s.result.clone()).collect();
+        assert_eq!(results , vec![
+            Some(AccessResult::MissSimple),
+            None,
+            Some(AccessResult::Hit),
+            Some(AccessResult::MissSimple),
+            Some(AccessResult::MissReplace(MissReplacement::new(1, 0, 2))),
+            Some(AccessResult::MissReplace(MissReplacement::new(2, 0, 3))),
+        ]);
+        assert_eq!(report.cow_faults() , 1);
+    }
+
+    #[test]
+    fn cow_faults_are_the_same_for_any_algorithm() {
+        let parsed = events("R:1 R:2 R:3 K:0:1 W:1:p=1 R:4 K:1:2 W:2:p=2 R:2 W:2 R:3:p=2 W:3:p=1 R:1 R:5:p=2 W:3");
+        for a in algorithms(3) {
+            let report = run(&a, 3, &parsed).unwrap();
+            assert_eq!(report.cow_faults() , 5, "{}", a.name);
+            let results = (a.run)(3, report.trace.clone()).unwrap();
+            assert_eq!(report.faults() + report.cow_faults() , get_total_faults(&results), "{}", a.name);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// LFU and MFU: count every reference to a page and evict the least (or most) used one. Ties go
+// to whichever page was used least recently. Counters can optionally be halved every so often
+// so pages that were hot a long time ago cool down.
+use {exit_on_error, parse_file, parse_number};
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+// Which end of the counts gets evicted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Least,
+    Most,
+}
+
+// What the comparison runs use when nobody tells us otherwise, 0 means counters never decay
+pub const DEFAULT_DECAY : usize = 0;
+
+// LFU and MFU parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct LFU_MFU_Params {
+    pub total_frames : usize,
+    // Halve every counter after this many accesses, 0 to never do it
+    pub decay : usize,
+    pub access_string : String,
+}
+
+impl LFU_MFU_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> LFU_MFU_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let frames = exit_on_error(parse_number("frame count", args.get(1)));
+        let decay = exit_on_error(parse_number("decay interval", args.get(2)));
+        let file = exit_on_error(parse_file(args.get(3)));
+        LFU_MFU_Params {
+            total_frames : frames,
+            decay,
+            access_string : file,
+        }
+    }
+}
+
+pub fn process_page_requests(frequency : Frequency , decay : usize , total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<Page>) -> Result<Vec<AccessResult>, String> {
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut pins = Pins::new();
+
+    for (clock, access) in accesses.iter().enumerate() {
+        pins.update(access);
+        // A freed page gives its frame up and its count goes with it
+        if access.access_type == AccessType::Free {
+            v_memory.retain(|page| page.number != access.frame_number);
+            results.push(AccessResult::Freed);
+        } else if let Some(index) = contains_page(access.frame_number, &v_memory) {
+            v_memory[index].count += 1;
+            v_memory[index].last_used = clock;
+            results.push(AccessResult::Hit);
+        } else if v_memory.len() < total_physical_pages {
+            // We have space, so it's a simple miss
+            v_memory.push(Page { number : access.frame_number, count : 1, last_used : clock });
+            results.push(AccessResult::MissSimple);
+        } else {
+            pins.check_evictable(total_physical_pages, access.frame_number)?;
+            let unpinned = v_memory.iter().enumerate().filter(|&(_, p)| !pins.contains(p.number));
+            let index = match frequency {
+                Frequency::Least => unpinned
+                    .min_by_key(|&(_, p)| (p.count, p.last_used)),
+                Frequency::Most => unpinned
+                    .min_by_key(|&(_, p)| (std::cmp::Reverse(p.count), p.last_used)),
+            }.map(|(i, _)| i).unwrap();
+            results.push(AccessResult::MissReplace(
+                MissReplacement::new(
+                    v_memory[index].number,
+                    index,
+                    access.frame_number)));
+            v_memory[index] = Page { number : access.frame_number, count : 1, last_used : clock };
+        }
+
+        if decay > 0 && (clock + 1) % decay == 0 {
+            for page in v_memory.iter_mut() {
+                page.count /= 2;
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+fn contains_page(page_num : usize , collection : &[Page]) -> Option<usize> {
+    collection.iter().position(|item| item.number == page_num)
+}
+
+// A reference count instead of a reference bit, plus when it was last used for breaking ties
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub number : usize,
+    pub count : usize,
+    pub last_used : usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        for frequency in &[Frequency::Least, Frequency::Most] {
+            let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+            let v_memory : Vec<Page> = Vec::with_capacity(4);
+            let results = process_page_requests(*frequency, 0, 4, accesses, v_memory).unwrap();
+            assert_eq!(results[0] , AccessResult::MissSimple);
+            assert_eq!(results[1] , AccessResult::MissSimple);
+            assert_eq!(results[2] , AccessResult::MissSimple);
+            assert_eq!(get_total_faults(&results) , 3);
+        }
+    }
+
+    #[test]
+    fn lfu_evicts_least_used_page() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 R:2 R:3 R:3 R:4"));
+        let v_memory : Vec<Page> = Vec::with_capacity(3);
+        let results = process_page_requests(Frequency::Least, 0, 3, accesses, v_memory).unwrap();
+        let mr = MissReplacement::new(2, 1, 4);
+        assert_eq!(results[5] , AccessResult::MissReplace(mr));
+    }
+
+    #[test]
+    fn lfu_ties_go_to_least_recently_used() {
+        // All counts are 1, 2 was used longer ago than 3 and 1 was touched twice
+        let accesses = MemoryAccess::create(String::from("R:2 R:1 R:1 R:3 R:4"));
+        let v_memory : Vec<Page> = Vec::with_capacity(3);
+        let results = process_page_requests(Frequency::Least, 0, 3, accesses, v_memory).unwrap();
+        let mr = MissReplacement::new(2, 0, 4);
+        assert_eq!(results[4] , AccessResult::MissReplace(mr));
+    }
+
+    #[test]
+    fn mfu_evicts_most_used_page() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:2 R:2 R:3 R:3 R:4"));
+        let v_memory : Vec<Page> = Vec::with_capacity(3);
+        let results = process_page_requests(Frequency::Most, 0, 3, accesses, v_memory).unwrap();
+        let mr = MissReplacement::new(2, 1, 4);
+        assert_eq!(results[6] , AccessResult::MissReplace(mr));
+    }
+
+    #[test]
+    fn decay_lets_old_hot_page_cool_down() {
+        // 1 is hot early on, then 2 and 3 are touched twice each
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 R:1 R:1 R:2 R:2 R:3 R:3 R:4"));
+
+        // Without decay 1 has 4 references against 2 each, so 2 (older than 3) goes
+        let v_memory : Vec<Page> = Vec::with_capacity(3);
+        let results = process_page_requests(Frequency::Least, 0, 3, accesses.clone(), v_memory).unwrap();
+        let mr = MissReplacement::new(2, 1, 4);
+        assert_eq!(results[8] , AccessResult::MissReplace(mr));
+
+        // Halving every 4 accesses takes 1 down from 4 to 1 by the time 4 comes in, the same as
+        // 2 and 3, and 1 is the one used longest ago
+        let v_memory : Vec<Page> = Vec::with_capacity(3);
+        let results = process_page_requests(Frequency::Least, 4, 3, accesses, v_memory).unwrap();
+        let mr = MissReplacement::new(1, 0, 4);
+        assert_eq!(results[8] , AccessResult::MissReplace(mr));
+    }
+}
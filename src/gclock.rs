@@ -0,0 +1,151 @@
+// Generalized CLOCK: Second Chance with a counter instead of a referenced bit. Hits bump the
+// counter up to a max, and the hand takes one off every page it passes, so a page hit n times
+// survives n sweeps. Pages start with the initial count when they're loaded.
+// With a max of 1 and an initial count of 1 it picks the same victims as Second Chance, and the
+// higher the max the more it leans towards LFU.
+use {exit_on_error, parse_file, parse_nonzero, parse_number};
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+// What the comparison runs use when nobody tells us otherwise
+pub const DEFAULT_MAX_COUNT : usize = 3;
+pub const DEFAULT_INITIAL_COUNT : usize = 1;
+
+// GCLOCK parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct GCLOCK_Params {
+    pub total_frames : usize,
+    pub max_count : usize,
+    pub initial_count : usize,
+    pub access_string : String,
+}
+
+impl GCLOCK_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> GCLOCK_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let frames = exit_on_error(parse_number("frame count", args.get(1)));
+        let max_count = exit_on_error(parse_nonzero("max count", args.get(2)));
+        let initial_count = exit_on_error(parse_number("initial count", args.get(3)).and_then(|c| {
+            if c <= max_count { Ok(c) } else { Err(String::from("The initial count can't be above the max count")) }
+        }));
+        let file = exit_on_error(parse_file(args.get(4)));
+        GCLOCK_Params {
+            total_frames : frames,
+            max_count,
+            initial_count,
+            access_string : file,
+        }
+    }
+}
+
+pub fn process_page_requests(max_count : usize , initial_count : usize , total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<Page>) -> Result<Vec<AccessResult>, String> {
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    // The clock pointer!
+    let mut hand = 0;
+    let mut pins = Pins::new();
+
+    for access in accesses.iter() {
+        pins.update(access);
+        let x = access.frame_number;
+        if access.access_type == AccessType::Free {
+            // The hand stays on the page it was on, or goes back to the start if that was the last
+            if let Some(index) = v_memory.iter().position(|p| p.number == x) {
+                v_memory.remove(index);
+                if index < hand {
+                    hand -= 1;
+                }
+                if hand >= v_memory.len() {
+                    hand = 0;
+                }
+            }
+            results.push(AccessResult::Freed);
+        } else if let Some(page) = v_memory.iter_mut().find(|p| p.number == x) {
+            page.count = (page.count + 1).min(max_count);
+            results.push(AccessResult::Hit);
+        } else if v_memory.len() < total_physical_pages {
+            v_memory.push(Page { number : x, count : initial_count });
+            results.push(AccessResult::MissSimple);
+        } else {
+            // Go around taking one off each page until one is at zero, pinned ones are passed over
+            pins.check_evictable(total_physical_pages, x)?;
+            while v_memory[hand].count > 0 || pins.contains(v_memory[hand].number) {
+                if !pins.contains(v_memory[hand].number) {
+                    v_memory[hand].count -= 1;
+                }
+                hand = (hand + 1) % v_memory.len();
+            }
+            results.push(AccessResult::MissReplace(MissReplacement::new(v_memory[hand].number, hand, x)));
+            v_memory[hand] = Page { number : x, count : initial_count };
+            hand = (hand + 1) % v_memory.len();
+        }
+    }
+
+    Ok(results)
+}
+
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub number : usize,
+    pub count : usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use random::Random;
+    use {get_total_faults, second};
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(3, 1, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 W:1 R:1 W:1"));
+        let total_pages = 2;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(3, 1, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        for result in results.iter().skip(1) {
+            assert_eq!(*result , AccessResult::Hit);
+        }
+    }
+
+    #[test]
+    fn page_hit_more_often_survives_more_sweeps() {
+        // Starting at 0, 1 is hit three times and 2 once. 2 runs out first and 3 takes its frame,
+        // and 1 still has a count left when 4 comes along, so 3 goes too.
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1 R:1 R:1 R:2 R:3 R:4"));
+        let total_pages = 2;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(3, 0, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[6] , AccessResult::MissReplace(MissReplacement::new(2, 1, 3)));
+        assert_eq!(results[7] , AccessResult::MissReplace(MissReplacement::new(3, 1, 4)));
+    }
+
+    #[test]
+    fn max_and_initial_of_one_is_second_chance() {
+        let mut random = Random::new(7);
+        let trace : Vec<String> = (0..200).map(|_| format!("R:{}", random.below(8))).collect();
+        let accesses = MemoryAccess::create(trace.join(" "));
+        let gclock = process_page_requests(1, 1, 4, accesses.clone(), Vec::with_capacity(4)).unwrap();
+        let second = second::process_page_requests(4, accesses, VecDeque::with_capacity(4)).unwrap();
+        let replaced = |results : &[AccessResult]| -> Vec<usize> {
+            results.iter().filter_map(|r| match *r {
+                AccessResult::MissReplace(ref mr) => Some(mr.replaced),
+                _ => None,
+            }).collect()
+        };
+        assert_eq!(replaced(&gclock) , replaced(&second));
+    }
+}
@@ -0,0 +1,335 @@
+// Mixed page sizes. Physical memory is a row of base frames, and a page takes as many of them as
+// its size calls for, contiguous and aligned to its own size, the way a 2 MiB page needs 512
+// aligned 4 KiB frames. The size goes in the trace as an s field in KiB, R:7:s=2048 is a 2 MiB
+// page, and pages without one are a single frame. A u field says how much of the page is actually
+// used, R:7:s=2048:u=600, the rest is internal fragmentation.
+// A fault goes in the first free window big enough. If there's none, the window whose most
+// recently used page is the oldest is emptied out, which is LRU when everything is one frame.
+// Windows with a pinned page in them are never emptied, and a freed page gives its frames back.
+use std::collections::HashMap;
+use {access_field, exit_on_error, parse_file, parse_nonzero, parse_number};
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+// Huge page parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct HUGEPAGE_Params {
+    pub total_frames : usize,
+    pub frame_kib : usize,
+    pub access_string : String,
+}
+
+impl HUGEPAGE_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> HUGEPAGE_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let frames = exit_on_error(parse_number("frame count", args.get(1)));
+        let frame_kib = exit_on_error(parse_nonzero("frame size", args.get(2)));
+        let file = exit_on_error(parse_file(args.get(3)));
+        HUGEPAGE_Params {
+            total_frames : frames,
+            frame_kib,
+            access_string : file,
+        }
+    }
+}
+
+// An access along with the size of its page
+#[derive(Debug, Clone)]
+pub struct SizedAccess {
+    pub access : MemoryAccess,
+    pub size_kib : usize,
+    pub used_kib : usize,
+}
+
+impl SizedAccess {
+    pub fn frames(&self , frame_kib : usize) -> usize {
+        self.size_kib / frame_kib
+    }
+}
+
+// Like MemoryAccess::create, but keeps the sizes, which have to be a power of two number of frames
+// and the same every time a page shows up. A free doesn't give a size, it frees the page at the
+// size it already has, and like everywhere else it can free a range of pages, F:3-6.
+pub fn parse_sized(input_string : &str , frame_kib : usize , total_frames : usize) -> Result<Vec<SizedAccess>, String> {
+    let mut sizes : HashMap<usize, usize> = HashMap::new();
+    let mut sized_accesses : Vec<SizedAccess> = Vec::new();
+    for token in input_string.split_whitespace() {
+        let parts : Vec<&str> = token.split(':').collect();
+        let invalid = || format!("Invalid memory access token: {}", token);
+        let access_type = match parts[0] {
+            "R" => AccessType::Read,
+            "W" => AccessType::Write,
+            "L" => AccessType::Lock,
+            "U" => AccessType::Unlock,
+            "F" => AccessType::Free,
+            _ => return Err(invalid()),
+        };
+        let number = |key : &str , default : usize| match access_field(token, key)? {
+            Some(n) => n.parse::<usize>().map_err(|_| invalid()),
+            None => Ok(default),
+        };
+        if access_type == AccessType::Free {
+            if parts.len() < 2 || access_field(token, "s")?.is_some() || access_field(token, "u")?.is_some() {
+                return Err(invalid());
+            }
+            let (first, last) = parts[1].split_once('-').unwrap_or((parts[1], parts[1]));
+            let first = first.parse::<usize>().map_err(|_| invalid())?;
+            let last = last.parse::<usize>().map_err(|_| invalid())?;
+            for page in first..=last {
+                let size_kib = sizes.get(&page).cloned().unwrap_or(frame_kib);
+                sized_accesses.push(SizedAccess { access : MemoryAccess { frame_number : page, access_type : AccessType::Free }, size_kib, used_kib : 0 });
+            }
+            continue;
+        }
+        let page = parts.get(1).ok_or_else(invalid)?.parse::<usize>().map_err(|_| invalid())?;
+        let size_kib = number("s", frame_kib)?;
+        let used_kib = number("u", size_kib)?;
+        let frames = size_kib / frame_kib;
+        if size_kib % frame_kib != 0 || !frames.is_power_of_two() {
+            return Err(format!("Page {} of {} KiB isn't a power of two number of {} KiB frames", page, size_kib, frame_kib));
+        }
+        if frames > total_frames {
+            return Err(format!("Page {} of {} KiB doesn't fit in memory", page, size_kib));
+        }
+        if used_kib > size_kib {
+            return Err(format!("Page {} uses more than its {} KiB", page, size_kib));
+        }
+        if *sizes.entry(page).or_insert(size_kib) != size_kib {
+            return Err(format!("Page {} shows up with two different sizes", page));
+        }
+        sized_accesses.push(SizedAccess { access : MemoryAccess { frame_number : page, access_type }, size_kib, used_kib });
+    }
+    Ok(sized_accesses)
+}
+
+#[derive(Debug, Clone)]
+struct Resident {
+    start : usize,
+    frames : usize,
+    last_used : usize,
+}
+
+// Gives an error when the pins leave no window for a page
+pub fn process_page_requests(frame_kib : usize , total_physical_pages : usize , accesses : &[SizedAccess]) -> Result<Vec<AccessResult>, String> {
+    Ok(process_page_requests_with_evictions(frame_kib, total_physical_pages, accesses)?.0)
+}
+
+// Same as above, but also hands back every page each access evicted, least recently used first.
+// A MissReplace names the first of those, and the frame its new page starts at. Pinned pages can
+// be scattered so that every window big enough has one in it even though there are frames to
+// spare, which can't be checked up front the way check_pins does, so that's an error.
+pub fn process_page_requests_with_evictions(frame_kib : usize , total_physical_pages : usize , accesses : &[SizedAccess]) -> Result<(Vec<AccessResult>, Vec<Vec<usize>>), String> {
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut evictions : Vec<Vec<usize>> = Vec::with_capacity(accesses.len());
+    // Base frame -> the page covering it
+    let mut frames : Vec<Option<usize>> = vec![None; total_physical_pages];
+    let mut resident : HashMap<usize, Resident> = HashMap::new();
+    let mut pins = Pins::new();
+
+    for (time, sized) in accesses.iter().enumerate() {
+        pins.update(&sized.access);
+        let x = sized.access.frame_number;
+        if sized.access.access_type == AccessType::Free {
+            if let Some(gone) = resident.remove(&x) {
+                for frame in frames.iter_mut().skip(gone.start).take(gone.frames) {
+                    *frame = None;
+                }
+            }
+            results.push(AccessResult::Freed);
+            evictions.push(Vec::new());
+            continue;
+        }
+        if let Some(page) = resident.get_mut(&x) {
+            page.last_used = time;
+            results.push(AccessResult::Hit);
+            evictions.push(Vec::new());
+            continue;
+        }
+
+        let size = sized.frames(frame_kib);
+        // Every aligned window without a pinned page, free ones first, then the one whose newest
+        // page is the oldest
+        let start = (0..total_physical_pages / size)
+            .map(|w| w * size)
+            .filter(|&start| frames[start..start + size].iter().all(|f| f.iter().all(|&p| !pins.contains(p))))
+            .min_by_key(|&start| {
+                let newest = frames[start..start + size].iter()
+                    .filter_map(|f| f.map(|p| resident[&p].last_used))
+                    .max();
+                (newest.is_some(), newest, start)
+            })
+            .ok_or_else(|| format!("Every window of {} frames has a pinned page in it, nothing can be evicted for page {} at access {}", size, x, time))?;
+
+        let mut evicted : Vec<usize> = frames[start..start + size].iter().filter_map(|&f| f).collect();
+        evicted.sort_by_key(|p| resident[p].last_used);
+        evicted.dedup();
+        for page in &evicted {
+            let gone = resident.remove(page).unwrap();
+            for frame in frames.iter_mut().skip(gone.start).take(gone.frames) {
+                *frame = None;
+            }
+        }
+        for frame in frames.iter_mut().skip(start).take(size) {
+            *frame = Some(x);
+        }
+        resident.insert(x, Resident { start, frames : size, last_used : time });
+
+        match evicted.first() {
+            Some(&first) => results.push(AccessResult::MissReplace(MissReplacement::new(first, start, x))),
+            None => results.push(AccessResult::MissSimple),
+        }
+        evictions.push(evicted);
+    }
+
+    Ok((results, evictions))
+}
+
+// How one page size did
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeSummary {
+    pub size_kib : usize,
+    pub accesses : usize,
+    pub faults : usize,
+    pub pages : usize,
+    // What the distinct pages take up and what they actually use, the difference is wasted
+    pub allocated_kib : usize,
+    pub used_kib : usize,
+}
+
+impl SizeSummary {
+    // Share of the allocated memory that's wasted, 0 to 1
+    pub fn fragmentation(&self) -> f64 {
+        if self.allocated_kib == 0 {
+            0.0
+        } else {
+            (self.allocated_kib - self.used_kib) as f64 / self.allocated_kib as f64
+        }
+    }
+}
+
+// One summary per page size, smallest first. A page counts as using the most it was ever said to.
+// Frees aren't accesses and don't count.
+pub fn summarize(accesses : &[SizedAccess] , results : &[AccessResult]) -> Vec<SizeSummary> {
+    let mut summaries : Vec<SizeSummary> = Vec::new();
+    let mut used : HashMap<usize, usize> = HashMap::new();
+    for (sized, result) in accesses.iter().zip(results.iter()) {
+        if *result == AccessResult::Freed {
+            continue;
+        }
+        let index = match summaries.iter().position(|s| s.size_kib == sized.size_kib) {
+            Some(i) => i,
+            None => {
+                summaries.push(SizeSummary { size_kib : sized.size_kib, accesses : 0, faults : 0, pages : 0, allocated_kib : 0, used_kib : 0 });
+                summaries.len() - 1
+            }
+        };
+        let summary = &mut summaries[index];
+        summary.accesses += 1;
+        if result.is_fault() {
+            summary.faults += 1;
+        }
+        let page_used = used.entry(sized.access.frame_number).or_insert_with(|| {
+            summary.pages += 1;
+            summary.allocated_kib += sized.size_kib;
+            0
+        });
+        if sized.used_kib > *page_used {
+            summary.used_kib += sized.used_kib - *page_used;
+            *page_used = sized.used_kib;
+        }
+    }
+    summaries.sort_by_key(|s| s.size_kib);
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = parse_sized("R:1 R:2 W:3", 4, 4).unwrap();
+        let results = process_page_requests(4, 4, &accesses).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn small_pages_only_is_lru() {
+        let accesses = parse_sized("R:1 R:2 R:3 R:1 R:4", 4, 3).unwrap();
+        let results = process_page_requests(4, 3, &accesses).unwrap();
+        assert_eq!(results[3] , AccessResult::Hit);
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(2, 1, 4)));
+    }
+
+    #[test]
+    fn huge_page_needs_an_aligned_window() {
+        // 3 leaves frame 1 free when it takes 9's place, but a huge page needs frames 0 and 1 or
+        // 2 and 3, so 1 and 2 have to go anyway
+        let accesses = parse_sized("R:9:s=8 R:1 R:2 R:3 R:10:s=8", 4, 4).unwrap();
+        let (results, evictions) = process_page_requests_with_evictions(4, 4, &accesses).unwrap();
+        assert_eq!(results[3] , AccessResult::MissReplace(MissReplacement::new(9, 0, 3)));
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(1, 2, 10)));
+        assert_eq!(evictions[4] , vec![1, 2]);
+    }
+
+    #[test]
+    fn huge_page_empties_the_least_recently_used_window() {
+        // 2 and 4 are the least recently used but they're not next to each other, 1 and 2 go
+        // since 1 was used before 3
+        let accesses = parse_sized("R:1 R:2 R:3 R:4 R:1 R:3 R:9:s=8", 4, 4).unwrap();
+        let (results, evictions) = process_page_requests_with_evictions(4, 4, &accesses).unwrap();
+        assert_eq!(results[6] , AccessResult::MissReplace(MissReplacement::new(2, 0, 9)));
+        assert_eq!(evictions[6] , vec![2, 1]);
+    }
+
+    #[test]
+    fn fragmentation_is_counted_per_size() {
+        let accesses = parse_sized("R:1 R:9:s=2048:u=512 R:1:u=2:s=4 R:9:s=2048:u=1024 R:10:s=2048", 4, 1024).unwrap();
+        let results = process_page_requests(4, 1024, &accesses).unwrap();
+        let summaries = summarize(&accesses, &results);
+        assert_eq!(summaries[0] , SizeSummary { size_kib : 4, accesses : 2, faults : 1, pages : 1, allocated_kib : 4, used_kib : 4 });
+        assert_eq!(summaries[1] , SizeSummary { size_kib : 2048, accesses : 3, faults : 2, pages : 2, allocated_kib : 4096, used_kib : 3072 });
+        assert_eq!(summaries[1].fragmentation() , 0.25);
+    }
+
+    #[test]
+    fn freed_huge_page_gives_its_window_back() {
+        let accesses = parse_sized("R:9:s=8 R:1 R:2 F:9 R:10:s=8", 4, 4).unwrap();
+        assert_eq!(accesses[3].size_kib , 8);
+        let (results, evictions) = process_page_requests_with_evictions(4, 4, &accesses).unwrap();
+        assert_eq!(results[3] , AccessResult::Freed);
+        assert_eq!(results[4] , AccessResult::MissSimple);
+        assert_eq!(evictions[4] , Vec::<usize>::new());
+        assert_eq!(summarize(&accesses, &results)[1].accesses , 2);
+    }
+
+    #[test]
+    fn windows_with_pinned_pages_are_never_emptied() {
+        // 1 is the least recently used, but its window is pinned so 2 and 3 go instead
+        let accesses = parse_sized("L:1 R:5 R:2 R:3 R:9:s=8", 4, 4).unwrap();
+        let (results, evictions) = process_page_requests_with_evictions(4, 4, &accesses).unwrap();
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(2, 2, 9)));
+        assert_eq!(evictions[4] , vec![2, 3]);
+
+        // Pinning a frame in each window leaves nowhere for a huge page, with half of memory free
+        let accesses = parse_sized("L:1 R:2 L:3 R:4 F:2 F:4 R:9:s=8", 4, 4).unwrap();
+        assert!(process_page_requests_with_evictions(4, 4, &accesses).is_err());
+    }
+
+    #[test]
+    fn bad_sizes_are_rejected() {
+        assert!(parse_sized("R:1:s=12", 4, 8).is_err());
+        assert!(parse_sized("R:1:s=6", 4, 8).is_err());
+        assert!(parse_sized("R:1:s=64", 4, 8).is_err());
+        assert!(parse_sized("R:1:s=4:u=8", 4, 8).is_err());
+        assert!(parse_sized("R:1:s=4 R:1:s=8", 4, 8).is_err());
+        assert!(parse_sized("X:1", 4, 8).is_err());
+        assert!(parse_sized("F:1:s=4", 4, 8).is_err());
+        assert!(parse_sized("R:1:4", 4, 8).is_err());
+    }
+}
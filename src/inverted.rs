@@ -0,0 +1,201 @@
+// Two page tables that grow with physical memory instead of the virtual address space, both sit
+// underneath any replacement algorithm by replaying its results.
+//   Inverted: one entry per frame holding the page that's in it (the v_memory Vec most of the
+//   algorithms keep), so finding a page means searching the frames in order
+//   Hashed: the page number hashes to a bucket and every bucket chains the pages in it, newest
+//   mapping at the front
+// Each lookup reports how many entries it had to look at, 1 at the least for reading an empty
+// slot or bucket.
+use {exit_on_error, parse_file, parse_nonzero, parse_number};
+use {AccessResult, MemoryAccess};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableKind {
+    Inverted,
+    // With this many buckets
+    Hashed(usize),
+}
+
+// Either inverted or hashed:N for N buckets
+pub fn parse_table_kind(kind_string : Option<&String>) -> Result<TableKind, String> {
+    match kind_string.map(|s| s.as_str()) {
+        None => Err(String::from("No page table kind provided")),
+        Some("inverted") => Ok(TableKind::Inverted),
+        Some(k) if k.starts_with("hashed:") => {
+            let buckets = k["hashed:".len()..].to_string();
+            parse_nonzero("bucket", Some(&buckets)).map(TableKind::Hashed)
+        }
+        Some(k) => Err(format!("Page table must be inverted or hashed:N, not {}", k)),
+    }
+}
+
+// Inverted and hashed page table parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct INVERTED_Params {
+    pub algorithm : String,
+    pub total_frames : usize,
+    pub tau : usize,
+    pub kind : TableKind,
+    pub access_string : String,
+}
+
+impl INVERTED_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> INVERTED_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let algorithm = exit_on_error(args.get(1).cloned().ok_or_else(|| String::from("No algorithm provided")));
+        let frames = exit_on_error(parse_number("frame count", args.get(2)));
+        let tau = exit_on_error(parse_number("tau", args.get(3)));
+        let kind = exit_on_error(parse_table_kind(args.get(4)));
+        let file = exit_on_error(parse_file(args.get(5)));
+        INVERTED_Params {
+            algorithm,
+            total_frames : frames,
+            tau,
+            kind,
+            access_string : file,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Table {
+    // Frame -> page, frames fill up in order and a freed frame is empty until the next fault
+    Inverted(Vec<Option<usize>>),
+    // Bucket -> chain of pages
+    Hashed(Vec<Vec<usize>>),
+}
+
+impl Table {
+    pub fn new(kind : TableKind) -> Table {
+        match kind {
+            TableKind::Inverted => Table::Inverted(Vec::new()),
+            TableKind::Hashed(buckets) => Table::Hashed(vec![Vec::new(); buckets]),
+        }
+    }
+
+    // How many entries the lookup looked at
+    pub fn probes(&self , page : usize) -> usize {
+        let (entries, found) = match *self {
+            Table::Inverted(ref frames) => (frames.len(), frames.iter().position(|&p| p == Some(page))),
+            Table::Hashed(ref buckets) => {
+                let chain = &buckets[page % buckets.len()];
+                (chain.len(), chain.iter().position(|&p| p == page))
+            }
+        };
+        match found {
+            Some(i) => i + 1,
+            None => entries.max(1),
+        }
+    }
+
+    // Maps the page, in the frame the evicted page leaves behind if there is one
+    pub fn map(&mut self , page : usize , evicted : Option<usize>) {
+        match *self {
+            Table::Inverted(ref mut frames) => {
+                let frame = match evicted {
+                    Some(e) => frames.iter().position(|&p| p == Some(e)),
+                    None => frames.iter().position(|p| p.is_none()),
+                };
+                match frame {
+                    Some(frame) => frames[frame] = Some(page),
+                    None => frames.push(Some(page)),
+                }
+            }
+            Table::Hashed(ref mut buckets) => {
+                let count = buckets.len();
+                if let Some(e) = evicted {
+                    buckets[e % count].retain(|&p| p != e);
+                }
+                buckets[page % count].insert(0, page);
+            }
+        }
+    }
+
+    // Takes a freed page out, its frame stays where it is
+    pub fn unmap(&mut self , page : usize) {
+        match *self {
+            Table::Inverted(ref mut frames) => {
+                if let Some(frame) = frames.iter().position(|&p| p == Some(page)) {
+                    frames[frame] = None;
+                }
+            }
+            Table::Hashed(ref mut buckets) => {
+                let count = buckets.len();
+                buckets[page % count].retain(|&p| p != page);
+            }
+        }
+    }
+}
+
+// Replays the results through the table and gives back the probes every access took. The frame a
+// replaced page was in is looked up by the page itself, not every algorithm fills in frame_index
+// the same way.
+pub fn probe_counts(kind : TableKind , accesses : &[MemoryAccess] , results : &[AccessResult]) -> Vec<usize> {
+    let mut table = Table::new(kind);
+    accesses.iter().zip(results.iter()).map(|(access, result)| {
+        // A free doesn't look anything up
+        if *result == AccessResult::Freed {
+            table.unmap(access.frame_number);
+            return 0;
+        }
+        let probes = table.probes(access.frame_number);
+        match *result {
+            AccessResult::Hit | AccessResult::PrefetchHit | AccessResult::Freed => (),
+            AccessResult::MissSimple => table.map(access.frame_number, None),
+            AccessResult::MissReplace(ref mr) => table.map(access.frame_number, Some(mr.replaced)),
+        }
+        probes
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compare::algorithm;
+    use {get_total_faults, MissReplacement};
+
+    #[test]
+    fn inverted_table_searches_the_frames_in_order() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:3 R:4 R:4"));
+        let results = vec![
+            AccessResult::MissSimple,
+            AccessResult::MissSimple,
+            AccessResult::MissSimple,
+            AccessResult::Hit,
+            AccessResult::MissReplace(MissReplacement::new(1, 0, 4)),
+            AccessResult::Hit,
+        ];
+        // 4 takes 1's frame, the first one
+        assert_eq!(probe_counts(TableKind::Inverted, &accesses, &results) , vec![1, 1, 2, 3, 3, 1]);
+    }
+
+    #[test]
+    fn hashed_table_only_searches_its_bucket() {
+        // 1, 3 and 5 share a bucket out of 2, 5 is the newest so it's found first
+        let accesses = MemoryAccess::create(String::from("R:1 R:3 R:2 R:5 R:1 R:5 R:2"));
+        let results = (algorithm("optimal", 0).unwrap().run)(4, accesses.clone()).unwrap();
+        assert_eq!(get_total_faults(&results) , 4);
+        assert_eq!(probe_counts(TableKind::Hashed(2), &accesses, &results) , vec![1, 1, 1, 2, 3, 1, 1]);
+        // A single bucket is a list of everything
+        assert_eq!(probe_counts(TableKind::Hashed(1), &accesses, &results) , vec![1, 1, 2, 3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn replaced_page_is_found_by_number_not_frame_index() {
+        // Second Chance always reports frame 0, but 1 replaces 2, which is in frame 1
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:2 R:1 R:3"));
+        let results = (algorithm("second", 0).unwrap().run)(2, accesses.clone()).unwrap();
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(2, 0, 1)));
+        assert_eq!(probe_counts(TableKind::Inverted, &accesses, &results) , vec![1, 1, 2, 2, 2, 1]);
+    }
+
+    #[test]
+    fn tables_can_be_parsed() {
+        assert_eq!(parse_table_kind(Some(&String::from("inverted"))) , Ok(TableKind::Inverted));
+        assert_eq!(parse_table_kind(Some(&String::from("hashed:16"))) , Ok(TableKind::Hashed(16)));
+        assert!(parse_table_kind(Some(&String::from("hashed:0"))).is_err());
+        assert!(parse_table_kind(Some(&String::from("radix"))).is_err());
+    }
+}
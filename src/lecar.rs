@@ -0,0 +1,346 @@
+// LeCaR and CACHEUS: two experts each pick a victim their own way, and which one gets to evict is
+// decided by a coin weighted by how much we trust them. Evicted pages are remembered in the
+// history of the expert that picked them, and when one faults back in that expert made a mistake,
+// so its weight goes down (more so the sooner the page came back).
+//   LeCaR's experts are LRU and LFU, with a fixed learning rate
+//   CACHEUS's experts are SR-LRU (pages that were never reused go first, so scans don't flush
+//   the cache) and CR-LFU (LFU, but ties go to the most recently used so churning pages don't
+//   push each other out). Its learning rate adapts by hill climbing on the hit rate of each
+//   window of accesses as big as memory.
+// All coin flips come from the seeded Random, so the same seed always gives the same run.
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use random::Random;
+use {exit_on_error, parse_file, parse_number};
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+// Which pair of experts and which learning rate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Learner {
+    Lecar,
+    Cacheus,
+}
+
+impl Learner {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Learner::Lecar => "lecar",
+            Learner::Cacheus => "cacheus",
+        }
+    }
+
+    // Recency expert first, frequency expert second
+    pub fn experts(&self) -> [&'static str; 2] {
+        match *self {
+            Learner::Lecar => ["lru", "lfu"],
+            Learner::Cacheus => ["sr_lru", "cr_lfu"],
+        }
+    }
+}
+
+// What the comparison runs use when nobody tells us otherwise
+pub const DEFAULT_SEED : u64 = 4017;
+// LeCaR's learning rate, and where CACHEUS starts from
+pub const LEARNING_RATE : f64 = 0.45;
+
+// LeCaR and CACHEUS parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct LECAR_Params {
+    pub total_frames : usize,
+    pub seed : u64,
+    pub access_string : String,
+    pub output : String,
+}
+
+impl LECAR_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> LECAR_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let frames = exit_on_error(parse_number("frame count", args.get(1)));
+        let seed = exit_on_error(parse_number("seed", args.get(2)));
+        let file = exit_on_error(parse_file(args.get(3)));
+        let output = exit_on_error(args.get(4).cloned().ok_or_else(|| String::from("No output file provided")));
+        LECAR_Params {
+            total_frames : frames,
+            seed : seed as u64,
+            access_string : file,
+            output,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub number : usize,
+    pub count : usize,
+    pub last_used : usize,
+    // Hit at least once since it was loaded
+    pub reused : bool,
+}
+
+// An evicted page in an expert's history
+#[derive(Debug, Clone)]
+pub struct Ghost {
+    pub number : usize,
+    pub evicted_at : usize,
+    pub count : usize,
+}
+
+// The state of the learner after an access
+#[derive(Debug, Clone, PartialEq)]
+pub struct Weights {
+    // Same order as Learner::experts
+    pub weights : [f64; 2],
+    pub learning_rate : f64,
+    // Which expert picked the victim, if there was one
+    pub evicted_by : Option<usize>,
+}
+
+pub fn process_page_requests(learner : Learner , seed : u64 , total_physical_pages : usize , accesses : Vec<MemoryAccess> , v_memory : Vec<Page>) -> Result<Vec<AccessResult>, String> {
+    Ok(process_page_requests_with_weights(learner, seed, total_physical_pages, accesses, v_memory)?.0)
+}
+
+// Same as above, but also hands back the weights after every access
+pub fn process_page_requests_with_weights(learner : Learner , seed : u64 , total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<Page>) -> Result<(Vec<AccessResult>, Vec<Weights>), String> {
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut steps : Vec<Weights> = Vec::with_capacity(accesses.len());
+    let mut random = Random::new(seed);
+    // How much a mistake still hurts after t accesses is discount^t, down to 0.005 after a
+    // memory's worth of accesses
+    let discount = 0.005f64.powf(1.0 / total_physical_pages.max(1) as f64);
+    let mut weights = [0.5, 0.5];
+    let mut learning_rate = LEARNING_RATE;
+    let mut histories : [VecDeque<Ghost>; 2] = Default::default();
+    let mut window = Window::new(learning_rate);
+    let mut pins = Pins::new();
+
+    for (time, access) in accesses.iter().enumerate() {
+        pins.update(access);
+        let x = access.frame_number;
+        let mut evicted_by = None;
+        // A freed page is forgotten by both experts, coming back later isn't anyone's mistake, and
+        // the window doesn't count it
+        if access.access_type == AccessType::Free {
+            v_memory.retain(|p| p.number != x);
+            for history in histories.iter_mut() {
+                history.retain(|g| g.number != x);
+            }
+            results.push(AccessResult::Freed);
+            steps.push(Weights { weights, learning_rate, evicted_by });
+            continue;
+        }
+        let hit = match v_memory.iter_mut().find(|p| p.number == x) {
+            Some(page) => {
+                page.count += 1;
+                page.last_used = time;
+                page.reused = true;
+                true
+            }
+            None => false,
+        };
+
+        if hit {
+            results.push(AccessResult::Hit);
+        } else {
+            // Whoever evicted it shouldn't have, it takes the blame and the page gets its count back
+            let mut count = 1;
+            for (expert, history) in histories.iter_mut().enumerate() {
+                if let Some(i) = history.iter().position(|g| g.number == x) {
+                    let ghost = history.remove(i).unwrap();
+                    let regret = discount.powf((time - ghost.evicted_at) as f64);
+                    weights[expert] *= (-learning_rate * regret).exp();
+                    count = ghost.count + 1;
+                }
+            }
+            let total = weights[0] + weights[1];
+            weights = [weights[0] / total, weights[1] / total];
+
+            let page = Page { number : x, count, last_used : time, reused : false };
+            if v_memory.len() < total_physical_pages {
+                v_memory.push(page);
+                results.push(AccessResult::MissSimple);
+            } else {
+                let expert = if random.next_f64() < weights[0] { 0 } else { 1 };
+                pins.check_evictable(total_physical_pages, x)?;
+                let index = victim(learner, expert, &v_memory, &pins);
+                let evicted = &v_memory[index];
+                histories[expert].push_back(Ghost { number : evicted.number, evicted_at : time, count : evicted.count });
+                if histories[expert].len() > total_physical_pages {
+                    histories[expert].pop_front();
+                }
+                results.push(AccessResult::MissReplace(MissReplacement::new(evicted.number, index, x)));
+                v_memory[index] = page;
+                evicted_by = Some(expert);
+            }
+        }
+
+        if learner == Learner::Cacheus {
+            window.record(hit, total_physical_pages.max(1), &mut learning_rate, &mut random);
+        }
+        steps.push(Weights { weights, learning_rate, evicted_by });
+    }
+
+    Ok((results, steps))
+}
+
+// The frame the expert would evict, never a pinned one
+pub fn victim(learner : Learner , expert : usize , v_memory : &[Page] , pins : &Pins) -> usize {
+    let pages = v_memory.iter().enumerate().filter(|&(_, p)| !pins.contains(p.number));
+    match (learner, expert) {
+        (Learner::Lecar, 0) => pages.min_by_key(|&(_, p)| p.last_used),
+        (Learner::Lecar, _) => pages.min_by_key(|&(_, p)| (p.count, p.last_used)),
+        (Learner::Cacheus, 0) => pages.min_by_key(|&(_, p)| (p.reused, p.last_used)),
+        (Learner::Cacheus, _) => pages.min_by_key(|&(_, p)| (p.count, Reverse(p.last_used))),
+    }.map(|(i, _)| i).unwrap()
+}
+
+// CACHEUS's hill climbing. At the end of every window the learning rate keeps moving the same way
+// if the hit rate went up last time it moved, and turns around if it went down. If nothing's been
+// hit for 10 windows in a row it starts over from somewhere random.
+#[derive(Debug, Clone)]
+struct Window {
+    hits : usize,
+    seen : usize,
+    previous_hit_rate : f64,
+    previous_learning_rate : f64,
+    empty_windows : usize,
+}
+
+impl Window {
+    fn new(learning_rate : f64) -> Window {
+        Window { hits : 0, seen : 0, previous_hit_rate : 0.0, previous_learning_rate : learning_rate, empty_windows : 0 }
+    }
+
+    fn record(&mut self , hit : bool , size : usize , learning_rate : &mut f64 , random : &mut Random) {
+        self.seen += 1;
+        if hit {
+            self.hits += 1;
+        }
+        if self.seen < size {
+            return;
+        }
+        let hit_rate = self.hits as f64 / self.seen as f64;
+        let hit_rate_change = hit_rate - self.previous_hit_rate;
+        let learning_rate_change = *learning_rate - self.previous_learning_rate;
+        self.previous_hit_rate = hit_rate;
+        self.previous_learning_rate = *learning_rate;
+        self.hits = 0;
+        self.seen = 0;
+
+        if hit_rate == 0.0 {
+            self.empty_windows += 1;
+            if self.empty_windows >= 10 {
+                *learning_rate = 0.001 + random.next_f64() * 0.999;
+                self.empty_windows = 0;
+            }
+            return;
+        }
+        self.empty_windows = 0;
+        if hit_rate_change == 0.0 && learning_rate_change != 0.0 {
+            return;
+        }
+        let up = learning_rate_change == 0.0 || (hit_rate_change > 0.0) == (learning_rate_change > 0.0);
+        let next = if up { *learning_rate * 1.5 } else { *learning_rate / 1.5 };
+        *learning_rate = next.clamp(0.001, 1.0);
+    }
+}
+
+// The weights after every access as JSON, written out by hand since all we need is numbers and
+// a few fixed names
+pub fn weights_json(learner : Learner , accesses : &[MemoryAccess] , results : &[AccessResult] , steps : &[Weights]) -> String {
+    let experts = learner.experts();
+    let mut json = String::from("{\n");
+    json.push_str(&format!("  \"policy\": \"{}\",\n", learner.name()));
+    json.push_str(&format!("  \"experts\": [\"{}\", \"{}\"],\n", experts[0], experts[1]));
+    json.push_str("  \"accesses\": [");
+    for (time, ((access, result), step)) in accesses.iter().zip(results.iter()).zip(steps.iter()).enumerate() {
+        let result = match *result {
+            AccessResult::Hit => "hit",
+            AccessResult::PrefetchHit => "prefetch_hit",
+            AccessResult::MissSimple => "miss_simple",
+            AccessResult::MissReplace(_) => "miss_replace",
+            AccessResult::Freed => "freed",
+        };
+        let evicted_by = match step.evicted_by {
+            Some(expert) => format!("\"{}\"", experts[expert]),
+            None => String::from("null"),
+        };
+        json.push_str(if time == 0 { "\n" } else { ",\n" });
+        json.push_str(&format!("    {{\"time\": {}, \"page\": {}, \"result\": \"{}\", \"weights\": {{\"{}\": {}, \"{}\": {}}}, \"learning_rate\": {}, \"evicted_by\": {}}}",
+                               time, access.frame_number, result, experts[0], step.weights[0], experts[1], step.weights[1],
+                               step.learning_rate, evicted_by));
+    }
+    json.push_str("\n  ]\n}\n");
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        for learner in &[Learner::Lecar, Learner::Cacheus] {
+            let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+            let v_memory : Vec<Page> = Vec::with_capacity(4);
+            let results = process_page_requests(*learner, 1, 4, accesses, v_memory).unwrap();
+            assert_eq!(results[0] , AccessResult::MissSimple);
+            assert_eq!(results[1] , AccessResult::MissSimple);
+            assert_eq!(results[2] , AccessResult::MissSimple);
+            assert_eq!(get_total_faults(&results) , 3);
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_victims_and_weights() {
+        let mut random = Random::new(3);
+        let trace : Vec<String> = (0..300).map(|_| format!("R:{}", random.below(10))).collect();
+        let accesses = MemoryAccess::create(trace.join(" "));
+        for learner in &[Learner::Lecar, Learner::Cacheus] {
+            let run = |seed| process_page_requests_with_weights(*learner, seed, 4, accesses.clone(), Vec::with_capacity(4)).unwrap();
+            let (results, steps) = run(9);
+            let (again, steps_again) = run(9);
+            assert_eq!(results , again);
+            assert_eq!(steps , steps_again);
+            for step in &steps {
+                assert!((step.weights[0] + step.weights[1] - 1.0).abs() < 1e-9);
+                assert!(step.learning_rate >= 0.001 && step.learning_rate <= 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn expert_whose_victim_comes_back_loses_weight() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1"));
+        for learner in &[Learner::Lecar, Learner::Cacheus] {
+            let (_, steps) = process_page_requests_with_weights(*learner, 5, 1, accesses.clone(), Vec::with_capacity(1)).unwrap();
+            let expert = steps[1].evicted_by.unwrap();
+            assert_eq!(steps[1].weights , [0.5, 0.5]);
+            assert!(steps[2].weights[expert] < 0.5);
+        }
+    }
+
+    #[test]
+    fn experts_pick_their_own_victims() {
+        let page = |number, count, last_used, reused| Page { number, count, last_used, reused };
+        // 1 is the oldest and reused, 2 and 3 were seen once, 3 most recently
+        let pages = vec![page(1, 3, 0, true), page(2, 1, 1, false), page(3, 1, 2, false)];
+        assert_eq!(victim(Learner::Lecar, 0, &pages, &Pins::new()) , 0);
+        assert_eq!(victim(Learner::Lecar, 1, &pages, &Pins::new()) , 1);
+        assert_eq!(victim(Learner::Cacheus, 0, &pages, &Pins::new()) , 1);
+        assert_eq!(victim(Learner::Cacheus, 1, &pages, &Pins::new()) , 2);
+    }
+
+    #[test]
+    fn json_has_one_entry_per_access() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1"));
+        let (results, steps) = process_page_requests_with_weights(Learner::Cacheus, 1, 2, accesses.clone(), Vec::with_capacity(2)).unwrap();
+        let json = weights_json(Learner::Cacheus, &accesses, &results, &steps);
+        assert!(json.contains("\"experts\": [\"sr_lru\", \"cr_lfu\"]"));
+        assert_eq!(json.matches("\"time\"").count() , 4);
+        assert!(json.contains("{\"time\": 0, \"page\": 1, \"result\": \"miss_simple\", \"weights\": {\"sr_lru\": 0.5, \"cr_lfu\": 0.5}"));
+        assert_eq!(json.matches('{').count() , json.matches('}').count());
+    }
+}
@@ -1,19 +1,79 @@
 
-// Corresponds to the R:N and W:N in the memory access text
-#[derive(Debug, PartialEq)]
+pub mod aging;
+pub mod analyze;
+pub mod arc;
+pub mod clockpro;
+pub mod compare;
+pub mod cow;
+pub mod frequency;
+pub mod gclock;
+pub mod hugepage;
+pub mod inverted;
+pub mod lecar;
+pub mod linux;
+pub mod lirs;
+pub mod lruk;
+pub mod mglru;
+pub mod nru;
+pub mod optimal;
+pub mod pagetable;
+pub mod prefetch;
+pub mod random;
+pub mod report;
+pub mod s3fifo;
+pub mod second;
+pub mod sieve;
+pub mod tiered;
+pub mod tlb;
+pub mod tune;
+pub mod twoq;
+pub mod wsclock;
+pub mod zswap;
+
+use std::collections::{HashSet, VecDeque};
+
+// Corresponds to the R:N and W:N in the memory access text, and L:N and U:N which read the page
+// like R:N but also lock it in memory or unlock it again. F:N frees the page, it isn't an access
+// at all, the page is just gone and its frame is free again. F:N-M frees N through M.
+#[derive(Debug, Clone, PartialEq)]
 pub enum AccessType {
     Read,
     Write,
+    Lock,
+    Unlock,
+    Free,
 }
 
 // Helps us keep track of the different results when trying to access a page
 // MissSimple simply means that we can push the page because we have space in physical memory
 // MissReplace is when we don't have space and need to evict a page
+// PrefetchHit is the first use of a page that's only there because a prefetcher brought it in
+// Freed is what an F:N gets, whether or not the page was resident
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum AccessResult {
     MissSimple,
     MissReplace(MissReplacement),
     Hit,
+    PrefetchHit,
+    Freed,
+}
+
+impl AccessResult {
+    // Either kind of hit, the page didn't have to be faulted in
+    pub fn is_hit(&self) -> bool {
+        match *self {
+            AccessResult::Hit | AccessResult::PrefetchHit => true,
+            AccessResult::MissSimple | AccessResult::MissReplace(_) | AccessResult::Freed => false,
+        }
+    }
+
+    // Either kind of miss, the page had to be faulted in
+    pub fn is_fault(&self) -> bool {
+        match *self {
+            AccessResult::MissSimple | AccessResult::MissReplace(_) => true,
+            AccessResult::Hit | AccessResult::PrefetchHit | AccessResult::Freed => false,
+        }
+    }
 }
 // This helps us track who was replaced, at what index, and which page replaced the old one
 // Did this so we can unit test and get accurate testing results
@@ -36,33 +96,66 @@ impl MissReplacement {
 }
 
 // Simple data structure to represent the R:N and W:N in the text
+#[derive(Debug, Clone, PartialEq)]
 pub struct MemoryAccess {
     pub frame_number : usize,
     pub access_type : AccessType,
 }
 
 impl MemoryAccess {
-    // Constructor to create a collection by parsing the input string
+    // Constructor to create a collection by parsing the input string, a range free turns into one
+    // free per page. K:P:C forks only mean something to cow, so they're skipped.
     pub fn create(input_string : String) -> Vec<MemoryAccess> {
+        let number = |n : &str| match n.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => panic!("Invalid memory access token: {}", e)
+        };
         let vals = input_string
             .split_whitespace()
+            .filter(|ss| !ss.starts_with("K:"))
             .map(|ss| ss.split(':'))
-            .map(|mut ps| MemoryAccess {
-                access_type : match ps.next().unwrap() {
+            .flat_map(|mut ps| {
+                let access_type = match ps.next().unwrap() {
                     "R" => AccessType::Read,
                     "W" => AccessType::Write,
+                    "L" => AccessType::Lock,
+                    "U" => AccessType::Unlock,
+                    "F" => AccessType::Free,
                     other => panic!("Invalid access token: {}", other),
-                },
-                frame_number : {
-                    match ps.next().unwrap().parse::<usize>() {
-                        Ok(n) => n,
-                        Err(e) => panic!("Invalid memory access token: {}", e)
-                    }
-                }});
+                };
+                let pages = ps.next().unwrap();
+                let (first, last) = match pages.split_once('-') {
+                    Some((first, last)) if access_type == AccessType::Free => (number(first), number(last)),
+                    _ => (number(pages), number(pages)),
+                };
+                (first..=last).map(move |frame_number| MemoryAccess { frame_number, access_type : access_type.clone() })
+            });
         vals.collect()
     }
 }
 
+// Tools that need to know more about an access than its page take it as KEY=VALUE fields after
+// the page, in any order, R:7:p=2:z=3.5. MemoryAccess::create skips over them (and over cow's
+// forks, so every process's page 7 is just page 7 to the other tools), so any trace works with any
+// tool and each one only looks at the keys it knows:
+//   p  the process making the access, for cow
+//   z  how many times smaller the page compresses, for zswap
+//   s  the size of the page in KiB, for hugepage
+//   u  how many KiB of the page are actually used, for hugepage
+// Gives back the value of the key if the token has it, and an error for a field that isn't
+// KEY=VALUE at all.
+pub fn access_field<'a>(token : &'a str , key : &str) -> Result<Option<&'a str>, String> {
+    let mut value = None;
+    for field in token.split(':').skip(2) {
+        match field.split_once('=') {
+            Some((k, v)) if k == key => value = Some(v),
+            Some(_) => (),
+            None => return Err(format!("Field {} in {} has to be KEY=VALUE", field, token)),
+        }
+    }
+    Ok(value)
+}
+
 // Optimal and Second Chance Parameters from the CLI args
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone)]
@@ -128,12 +221,20 @@ impl WSCPR_Params {
     }
 }
 
-pub fn get_total_faults(results : &Vec<AccessResult>) -> usize {
+pub fn get_total_faults(results : &[AccessResult]) -> usize {
     results.iter()
-        .filter(|r| **r != AccessResult::Hit)
+        .filter(|r| r.is_fault())
         .count()
 }
 
+// Unwraps a CLI parsing result, or prints the error and exits like the Params constructors do
+pub fn exit_on_error<T>(result : Result<T, String>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("Args Error: {}", e);
+        std::process::exit(1);
+    })
+}
+
 pub fn parse_file(filename : Option<&String>) -> Result<String, String> {
     match filename {
         None => Err(String::from("No filename provided")),
@@ -147,3 +248,98 @@ pub fn parse_number(num_kind : &str , num_string : Option<&String>) -> Result<us
         Some(a) => a.parse::<usize>().map_err(|_| format!("Invalid {} count provided", num_kind)),
     }
 }
+
+// For counts where 0 makes no sense, like an interval we take a modulo by
+pub fn parse_nonzero(num_kind : &str , num_string : Option<&String>) -> Result<usize, String> {
+    match parse_number(num_kind, num_string)? {
+        0 => Err(format!("The {} has to be at least 1", num_kind)),
+        n => Ok(n),
+    }
+}
+
+// Pages locked with L:N until a U:N unlocks them, no algorithm is allowed to evict them. Every
+// algorithm updates it at the start of each access and skips pinned pages when looking for a victim.
+#[derive(Debug, Clone, Default)]
+pub struct Pins {
+    pinned : HashSet<usize>,
+}
+
+impl Pins {
+    pub fn new() -> Pins {
+        Pins { pinned : HashSet::new() }
+    }
+
+    pub fn update(&mut self , access : &MemoryAccess) {
+        match access.access_type {
+            AccessType::Lock => { self.pinned.insert(access.frame_number); }
+            // Freeing a page unlocks it too
+            AccessType::Unlock | AccessType::Free => { self.pinned.remove(&access.frame_number); }
+            AccessType::Read | AccessType::Write => (),
+        }
+    }
+
+    pub fn contains(&self , page : usize) -> bool {
+        self.pinned.contains(&page)
+    }
+
+    // Takes the first page that isn't pinned out of a list kept LRU first, for the algorithms that
+    // would otherwise just pop the front
+    pub fn pop_unpinned(&self , list : &mut VecDeque<usize>) -> Option<usize> {
+        let index = list.iter().position(|&p| !self.contains(p))?;
+        list.remove(index)
+    }
+
+    // Called before looking for a victim for the page, so a trace that pins every frame stops
+    // with an error instead of looping forever. check_pins catches the same thing up front.
+    pub fn check_evictable(&self , total_physical_pages : usize , page : usize) -> Result<(), String> {
+        let others = self.pinned.iter().filter(|&&p| p != page).count();
+        if others > 0 && others >= total_physical_pages {
+            return Err(format!("All {} frames are pinned, nothing can be evicted for page {}", total_physical_pages, page));
+        }
+        Ok(())
+    }
+}
+
+// Pinned pages are always resident, so a fault on any other page when they fill every frame has
+// nothing left to evict, whatever the algorithm. A free never faults, and neither does unlocking a
+// pinned page since it's resident, but unlocking any other page reads it like R.
+pub fn check_pins(total_physical_pages : usize , accesses : &[MemoryAccess]) -> Result<(), String> {
+    let mut pins = Pins::new();
+    for (i, access) in accesses.iter().enumerate() {
+        let was_pinned = pins.contains(access.frame_number);
+        pins.update(access);
+        match access.access_type {
+            AccessType::Free => continue,
+            AccessType::Unlock if was_pinned => continue,
+            _ => (),
+        }
+        let others = pins.pinned.iter().filter(|&&p| p != access.frame_number).count();
+        if others > 0 && others >= total_physical_pages {
+            return Err(format!("All {} frames are pinned when page {} faults at access {}", total_physical_pages, access.frame_number, i));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_pins_only_stops_faults() {
+        let accesses = MemoryAccess::create(String::from("L:1 L:2 F:3 U:2 L:2"));
+        assert_eq!(check_pins(2, &accesses) , Ok(()));
+        let accesses = MemoryAccess::create(String::from("L:1 L:2 U:3"));
+        assert_eq!(check_pins(2, &accesses) , Err(String::from("All 2 frames are pinned when page 3 faults at access 2")));
+    }
+
+    #[test]
+    fn cow_traces_work_everywhere() {
+        let accesses = MemoryAccess::create(String::from("R:1 K:0:2 W:1:p=2 L:3:p=2"));
+        assert_eq!(accesses , vec![
+            MemoryAccess { frame_number : 1, access_type : AccessType::Read },
+            MemoryAccess { frame_number : 1, access_type : AccessType::Write },
+            MemoryAccess { frame_number : 3, access_type : AccessType::Lock },
+        ]);
+    }
+}
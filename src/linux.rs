@@ -0,0 +1,219 @@
+// A model of the Linux kernel's active and inactive LRU lists. New pages start on the inactive
+// list, the first reference there only sets the referenced flag and the second one promotes the
+// page to the active list. Reclaim happens at the tail of the inactive list, which gets refilled
+// from the tail of the active list whenever the active list grows bigger than it.
+//
+// Evicted pages leave a shadow entry with the eviction age. When one of them faults back in, the
+// refault distance (how many evictions and activations happened since) tells us whether it would
+// have stayed resident had the active list given up its space, if so it goes straight to active.
+//
+// Dirty pages that reach the tail of the inactive list are written back and rotated instead of
+// reclaimed, so clean pages go first and no dirty page is ever dropped.
+//
+// Pinned pages that reclaim comes across are moved to the unevictable list, where it doesn't look
+// anymore, and they go back to the inactive list once they're unlocked.
+//
+// A freed page comes off whichever list it's on without being written back, and leaves no shadow.
+use std::collections::{HashMap, VecDeque};
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+pub fn process_page_requests(total_physical_pages : usize , accesses : Vec<MemoryAccess> , v_memory : Vec<usize>) -> Result<Vec<AccessResult>, String> {
+    Ok(process_page_requests_with_writes(total_physical_pages, accesses, v_memory)?.0)
+}
+
+// Same as above, but also hands back every page that was written back, in order
+pub fn process_page_requests_with_writes(total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<usize>) -> Result<(Vec<AccessResult>, Vec<usize>), String> {
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut lists = Lists::default();
+    let mut pins = Pins::new();
+
+    for access in accesses.iter() {
+        pins.update(access);
+        let x = access.frame_number;
+        let is_write = access.access_type == AccessType::Write;
+        if access.access_type == AccessType::Free {
+            lists.active.retain(|p| p.number != x);
+            lists.inactive.retain(|p| p.number != x);
+            lists.unevictable.retain(|p| p.number != x);
+            lists.shadows.remove(&x);
+            v_memory.retain(|&p| p != x);
+            results.push(AccessResult::Freed);
+            continue;
+        }
+        if let Some(i) = lists.unevictable.iter().position(|p| p.number == x) {
+            lists.unevictable[i].is_dirty |= is_write;
+            if !pins.contains(x) {
+                let page = lists.unevictable.remove(i);
+                lists.inactive.push_back(page);
+            }
+            results.push(AccessResult::Hit);
+            continue;
+        }
+        if let Some(page) = lists.active.iter_mut().find(|p| p.number == x) {
+            page.referenced = true;
+            page.is_dirty |= is_write;
+            results.push(AccessResult::Hit);
+            continue;
+        }
+        if let Some(i) = lists.inactive.iter().position(|p| p.number == x) {
+            // mark_page_accessed: the second reference activates it
+            lists.inactive[i].is_dirty |= is_write;
+            if lists.inactive[i].referenced {
+                let page = lists.inactive.remove(i).unwrap();
+                lists.activate(page);
+            } else {
+                lists.inactive[i].referenced = true;
+            }
+            results.push(AccessResult::Hit);
+            continue;
+        }
+
+        if v_memory.len() < total_physical_pages {
+            v_memory.push(x);
+            results.push(AccessResult::MissSimple);
+        } else {
+            pins.check_evictable(total_physical_pages, x)?;
+            let victim = lists.reclaim(&pins);
+            let index = v_memory.iter().position(|&p| p == victim).unwrap();
+            results.push(AccessResult::MissReplace(MissReplacement::new(victim, index, x)));
+            v_memory[index] = x;
+        }
+
+        let page = Page { number : x, referenced : false, is_dirty : is_write };
+        match lists.shadows.remove(&x) {
+            Some(evicted_at) if lists.age - evicted_at <= lists.active.len() => lists.activate(page),
+            _ => lists.inactive.push_back(page),
+        }
+    }
+
+    Ok((results, lists.writes))
+}
+
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub number : usize,
+    pub referenced : bool,
+    pub is_dirty : bool,
+}
+
+// Tail (oldest) at the front and head at the back for both lists
+#[derive(Debug, Clone, Default)]
+pub struct Lists {
+    pub active : VecDeque<Page>,
+    pub inactive : VecDeque<Page>,
+    // Page -> age when it was evicted
+    pub shadows : HashMap<usize, usize>,
+    // Pinned pages reclaim found, in no particular order
+    pub unevictable : Vec<Page>,
+    // Ticks on every eviction and every activation, like the kernel's nonresident age
+    pub age : usize,
+    pub writes : Vec<usize>,
+}
+
+impl Lists {
+    fn activate(&mut self , mut page : Page) {
+        page.referenced = false;
+        self.active.push_back(page);
+        self.age += 1;
+    }
+
+    // Moves pages from the active tail to the inactive head until the inactive list is at least as
+    // big, referenced ones get another trip around the active list first
+    fn balance(&mut self) {
+        while self.active.len() > self.inactive.len() {
+            let mut page = self.active.pop_front().unwrap();
+            if page.referenced {
+                page.referenced = false;
+                self.active.push_back(page);
+            } else {
+                self.inactive.push_back(page);
+            }
+        }
+    }
+
+    // Frees up a frame and gives back the page that was in it
+    fn reclaim(&mut self , pins : &Pins) -> usize {
+        loop {
+            self.balance();
+            let mut page = match self.inactive.pop_front() {
+                Some(page) => page,
+                None => {
+                    // Everything's active and referenced, the balance above only rotated them
+                    let mut page = self.active.pop_front().unwrap();
+                    page.referenced = false;
+                    page
+                }
+            };
+            if pins.contains(page.number) {
+                self.unevictable.push(page);
+            } else if page.referenced {
+                // Used once since it got here, give it another lap
+                page.referenced = false;
+                self.inactive.push_back(page);
+            } else if page.is_dirty {
+                // Write it back and come around for it later, it'll be clean by then
+                self.writes.push(page.number);
+                page.is_dirty = false;
+                self.inactive.push_back(page);
+            } else {
+                self.shadows.insert(page.number, self.age);
+                self.age += 1;
+                return page.number;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn second_reference_promotes_to_active() {
+        // 1 is referenced twice and goes active, 2 only once and gets another lap, 3 goes
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1 R:1 R:2 R:4"));
+        let total_pages = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        let mr = MissReplacement::new(3, 2, 4);
+        assert_eq!(results[6] , AccessResult::MissReplace(mr));
+    }
+
+    #[test]
+    fn dirty_page_is_written_back_and_a_clean_one_reclaimed_first() {
+        let accesses = MemoryAccess::create(String::from("W:1 R:2 R:3"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let (results, writes) = process_page_requests_with_writes(total_pages, accesses, v_memory).unwrap();
+        let mr = MissReplacement::new(2, 1, 3);
+        assert_eq!(results[2] , AccessResult::MissReplace(mr));
+        assert_eq!(writes , vec![1]);
+    }
+
+    #[test]
+    fn quick_refault_goes_straight_to_active() {
+        // 2, 3 and 6 are active, 1 is evicted by 4 and faults back in two evictions later, which
+        // is within the size of the active list, so it skips the inactive list and is still there at the end
+        let accesses = MemoryAccess::create(String::from(
+            "R:2 R:2 R:2 R:3 R:3 R:3 R:6 R:6 R:6 R:1 R:4 R:1 R:5 R:7 R:1"));
+        let total_pages = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[10] , AccessResult::MissReplace(MissReplacement::new(1, 3, 4)));
+        assert_ne!(results[11] , AccessResult::Hit);
+        assert_eq!(results[14] , AccessResult::Hit);
+    }
+}
@@ -0,0 +1,269 @@
+// Low Inter-reference Recency Set: pages whose last two references were close together are LIR
+// and keep their frames, everything else is HIR and fights over a small slice of memory. The
+// stack S orders pages by recency (including HIR pages that already lost their frame, so we still
+// know their recency when they come back), the queue Q holds the resident HIR pages in eviction
+// order. Loops a bit bigger than memory make LRU miss every time, LIRS keeps most of the loop.
+use std::collections::{HashMap, VecDeque};
+use {exit_on_error, parse_file, parse_number};
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+// What the comparison runs use when nobody tells us otherwise
+pub const DEFAULT_HIR_PERCENT : usize = 1;
+
+// LIRS parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct LIRS_Params {
+    pub total_frames : usize,
+    // Percentage of frames kept for resident HIR pages, at least one frame either way
+    pub hir_percent : usize,
+    pub access_string : String,
+}
+
+impl LIRS_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> LIRS_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let frames = exit_on_error(parse_number("frame count", args.get(1)));
+        let hir_percent = exit_on_error(parse_number("HIR percent", args.get(2)).and_then(|p| {
+            if p < 100 { Ok(p) } else { Err(String::from("HIR percent has to be below 100")) }
+        }));
+        let file = exit_on_error(parse_file(args.get(3)));
+        LIRS_Params {
+            total_frames : frames,
+            hir_percent,
+            access_string : file,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Lir,
+    HirResident,
+    HirNonResident,
+}
+
+// Who was LIR and who was HIR among the resident pages when a page got evicted
+#[derive(Debug, Clone, PartialEq)]
+pub struct Eviction {
+    pub time : usize,
+    pub evicted : usize,
+    pub lir : Vec<usize>,
+    pub hir : Vec<usize>,
+}
+
+pub fn process_page_requests(hir_percent : usize , total_physical_pages : usize , accesses : Vec<MemoryAccess> , v_memory : Vec<usize>) -> Result<Vec<AccessResult>, String> {
+    Ok(process_page_requests_with_status(hir_percent, total_physical_pages, accesses, v_memory)?.0)
+}
+
+// Same as above, but also hands back the LIR/HIR status of the resident pages at every eviction
+pub fn process_page_requests_with_status(hir_percent : usize , total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<usize>) -> Result<(Vec<AccessResult>, Vec<Eviction>), String> {
+    let hir_frames = (total_physical_pages * hir_percent / 100).max(1).min(total_physical_pages);
+    let lir_frames = total_physical_pages - hir_frames;
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut evictions : Vec<Eviction> = Vec::new();
+    // Bottom of the stack at the front, top at the back
+    let mut stack : VecDeque<usize> = VecDeque::new();
+    // Next HIR page to be evicted at the front
+    let mut queue : VecDeque<usize> = VecDeque::new();
+    let mut status : HashMap<usize, Status> = HashMap::new();
+    let mut lir_count = 0;
+    let mut pins = Pins::new();
+
+    for (time, access) in accesses.iter().enumerate() {
+        pins.update(access);
+        let x = access.frame_number;
+        let in_stack = remove(&mut stack, x);
+        // A freed page is forgotten, a LIR one leaves room for the next new page to be LIR
+        if access.access_type == AccessType::Free {
+            if status.remove(&x) == Some(Status::Lir) {
+                lir_count -= 1;
+            }
+            remove(&mut queue, x);
+            prune(&mut stack, &status);
+            v_memory.retain(|&p| p != x);
+            results.push(AccessResult::Freed);
+            continue;
+        }
+        match status.get(&x).cloned() {
+            Some(Status::Lir) => {
+                stack.push_back(x);
+                prune(&mut stack, &status);
+                results.push(AccessResult::Hit);
+            }
+            Some(Status::HirResident) => {
+                if in_stack && lir_frames > 0 {
+                    // Its reuse happened while it was still on the stack, so it's LIR now and the
+                    // LIR page at the bottom has to step down
+                    remove(&mut queue, x);
+                    status.insert(x, Status::Lir);
+                    stack.push_back(x);
+                    demote_bottom(&mut stack, &mut queue, &mut status);
+                } else {
+                    remove(&mut queue, x);
+                    queue.push_back(x);
+                    stack.push_back(x);
+                }
+                results.push(AccessResult::Hit);
+            }
+            miss => {
+                // Make room first if we have to, the front of the queue goes
+                if v_memory.len() < total_physical_pages {
+                    v_memory.push(x);
+                    results.push(AccessResult::MissSimple);
+                } else {
+                    // Pinned HIR pages can't go, if that's all of them LIR pages step down until
+                    // there's one that can
+                    pins.check_evictable(total_physical_pages, x)?;
+                    while queue.iter().all(|&p| pins.contains(p)) {
+                        demote_bottom(&mut stack, &mut queue, &mut status);
+                        lir_count -= 1;
+                    }
+                    let victim = pins.pop_unpinned(&mut queue).unwrap();
+                    let mut lir : Vec<usize> = Vec::new();
+                    let mut hir : Vec<usize> = Vec::new();
+                    for page in &v_memory {
+                        match status[page] {
+                            Status::Lir => lir.push(*page),
+                            _ => hir.push(*page),
+                        }
+                    }
+                    evictions.push(Eviction { time, evicted : victim, lir, hir });
+                    if stack.contains(&victim) {
+                        status.insert(victim, Status::HirNonResident);
+                    } else {
+                        status.remove(&victim);
+                    }
+                    let index = v_memory.iter().position(|&p| p == victim).unwrap();
+                    results.push(AccessResult::MissReplace(MissReplacement::new(victim, index, x)));
+                    v_memory[index] = x;
+                }
+
+                if lir_count < lir_frames {
+                    // Still warming up, there's room for more LIR pages
+                    status.insert(x, Status::Lir);
+                    lir_count += 1;
+                    stack.push_back(x);
+                } else if miss == Some(Status::HirNonResident) && in_stack && lir_frames > 0 {
+                    status.insert(x, Status::Lir);
+                    stack.push_back(x);
+                    demote_bottom(&mut stack, &mut queue, &mut status);
+                } else {
+                    status.insert(x, Status::HirResident);
+                    stack.push_back(x);
+                    queue.push_back(x);
+                }
+            }
+        }
+        // Pages that are neither resident nor on the stack are forgotten
+        status.retain(|page, s| *s != Status::HirNonResident || stack.contains(page));
+    }
+
+    Ok((results, evictions))
+}
+
+// Takes the page out of the list if it's there
+fn remove(list : &mut VecDeque<usize> , page : usize) -> bool {
+    match list.iter().position(|&p| p == page) {
+        Some(i) => {
+            list.remove(i);
+            true
+        }
+        None => false,
+    }
+}
+
+// The LIR page at the bottom of the stack becomes a resident HIR page at the end of the queue
+fn demote_bottom(stack : &mut VecDeque<usize> , queue : &mut VecDeque<usize> , status : &mut HashMap<usize, Status>) {
+    prune(stack, status);
+    if let Some(bottom) = stack.pop_front() {
+        status.insert(bottom, Status::HirResident);
+        queue.push_back(bottom);
+    }
+    prune(stack, status);
+}
+
+// Stack pruning, HIR pages at the bottom go until a LIR page is there
+fn prune(stack : &mut VecDeque<usize> , status : &HashMap<usize, Status>) {
+    while let Some(&bottom) = stack.front() {
+        if status.get(&bottom) == Some(&Status::Lir) {
+            break;
+        }
+        stack.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {get_total_faults, second};
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(1, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 W:1 R:1 W:1"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(1, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        for result in results.iter().skip(1) {
+            assert_eq!(*result , AccessResult::Hit);
+        }
+    }
+
+    #[test]
+    fn evicts_the_hir_page_and_reports_statuses() {
+        // 3 frames with 1 for HIR, 1 and 2 become LIR, 3 is the resident HIR page
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4"));
+        let total_pages = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let (results, evictions) = process_page_requests_with_status(1, total_pages, accesses, v_memory).unwrap();
+
+        // Replaced R:3 at index 2 with R:4
+        let mr = MissReplacement::new(3, 2, 4);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+        assert_eq!(evictions , vec![Eviction { time : 3, evicted : 3, lir : vec![1, 2], hir : vec![3] }]);
+    }
+
+    #[test]
+    fn non_resident_hir_page_coming_back_becomes_lir() {
+        // 3 gets evicted but stays on the stack, when it comes back its recency beats 1's
+        // so 1 (the LIR page at the bottom) becomes HIR
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:2 R:3 R:5"));
+        let total_pages = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let (results, evictions) = process_page_requests_with_status(1, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[4] , AccessResult::Hit);
+        assert_eq!(results[5] , AccessResult::MissReplace(MissReplacement::new(4, 2, 3)));
+        assert_eq!(results[6] , AccessResult::MissReplace(MissReplacement::new(1, 0, 5)));
+        assert_eq!(evictions[2].lir , vec![2, 3]);
+        assert_eq!(evictions[2].hir , vec![1]);
+    }
+
+    #[test]
+    fn loop_bigger_than_memory_still_hits_unlike_second_chance() {
+        let mut trace : Vec<String> = Vec::new();
+        for _ in 0..10 {
+            for page in 1..6 {
+                trace.push(format!("R:{}", page));
+            }
+        }
+        let accesses = MemoryAccess::create(trace.join(" "));
+        let lirs = process_page_requests(1, 4, accesses.clone(), Vec::with_capacity(4)).unwrap();
+        let second = second::process_page_requests(4, accesses, VecDeque::with_capacity(4)).unwrap();
+        assert_eq!(get_total_faults(&second) , 50);
+        assert!(get_total_faults(&lirs) < 25);
+    }
+}
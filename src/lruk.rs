@@ -0,0 +1,235 @@
+// LRU-K: evicts the page whose Kth most recent reference is the furthest back, pages that haven't
+// been referenced K times yet count as infinitely far back and go first (LRU among themselves).
+// With K=1 this is plain LRU, with K=2 a page has to come back before it's worth keeping, so one
+// pass of a scan can't push out pages that are used over and over.
+// References closer together than the correlated reference period are one burst and only count
+// once, and pages inside their period can't be evicted. Evicted pages keep their history (up to a
+// bound) so a page that comes back isn't starting from scratch.
+use std::collections::HashMap;
+use {exit_on_error, parse_file, parse_nonzero, parse_number};
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+// What the comparison runs use when nobody tells us otherwise
+pub const DEFAULT_K : usize = 2;
+pub const DEFAULT_CORRELATED_PERIOD : usize = 0;
+pub const DEFAULT_RETAINED : usize = 32;
+
+// LRU-K parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct LRUK_Params {
+    pub total_frames : usize,
+    pub k : usize,
+    // References this many accesses apart or closer are the same burst
+    pub correlated_period : usize,
+    // Most histories of evicted pages we hold on to
+    pub retained : usize,
+    pub access_string : String,
+}
+
+impl LRUK_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> LRUK_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let frames = exit_on_error(parse_number("frame count", args.get(1)));
+        let k = exit_on_error(parse_nonzero("K", args.get(2)));
+        let correlated_period = exit_on_error(parse_number("correlated reference period", args.get(3)));
+        let retained = exit_on_error(parse_number("retained history count", args.get(4)));
+        let file = exit_on_error(parse_file(args.get(5)));
+        LRUK_Params {
+            total_frames : frames,
+            k,
+            correlated_period,
+            retained,
+            access_string : file,
+        }
+    }
+}
+
+// The reference history of a page, resident or not
+#[derive(Debug, Clone)]
+pub struct History {
+    // Most recent uncorrelated reference first, None for the ones that never happened
+    pub times : Vec<Option<usize>>,
+    // The very last reference, correlated or not
+    pub last : usize,
+}
+
+pub fn process_page_requests(k : usize , correlated_period : usize , retained : usize , total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<usize>) -> Result<Vec<AccessResult>, String> {
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut histories : HashMap<usize, History> = HashMap::new();
+    let mut pins = Pins::new();
+
+    for (time, access) in accesses.iter().enumerate() {
+        pins.update(access);
+        let x = access.frame_number;
+        // A freed page loses its history too, if it comes back it's a new page
+        if access.access_type == AccessType::Free {
+            v_memory.retain(|&p| p != x);
+            histories.remove(&x);
+            results.push(AccessResult::Freed);
+            continue;
+        }
+        if v_memory.contains(&x) {
+            let history = histories.get_mut(&x).unwrap();
+            if time - history.last > correlated_period {
+                // A new burst. The one that just ended only counts as its first reference, so the
+                // older ones move up by however long it lasted.
+                let burst = history.last - history.times[0].unwrap();
+                for i in (1..k).rev() {
+                    history.times[i] = history.times[i - 1].map(|t| t + burst);
+                }
+                history.times[0] = Some(time);
+            }
+            history.last = time;
+            results.push(AccessResult::Hit);
+            continue;
+        }
+
+        if v_memory.len() < total_physical_pages {
+            v_memory.push(x);
+            results.push(AccessResult::MissSimple);
+        } else {
+            pins.check_evictable(total_physical_pages, x)?;
+            let index = victim(&v_memory, &histories, k, correlated_period, time, &pins);
+            let victim = v_memory[index];
+            results.push(AccessResult::MissReplace(MissReplacement::new(victim, index, x)));
+            v_memory[index] = x;
+            forget_oldest(&mut histories, &v_memory, retained);
+        }
+
+        let history = histories.entry(x).or_insert(History { times : vec![None; k], last : time });
+        for i in (1..k).rev() {
+            history.times[i] = history.times[i - 1];
+        }
+        history.times[0] = Some(time);
+        history.last = time;
+    }
+
+    Ok(results)
+}
+
+// The frame whose page has the oldest Kth reference, leaving out pages still in their correlated
+// period unless that's all of them, ties go to the least recently used. Pinned pages never count.
+fn victim(v_memory : &[usize] , histories : &HashMap<usize, History> , k : usize , correlated_period : usize , time : usize , pins : &Pins) -> usize {
+    let key = |&(_, page) : &(usize, &usize)| {
+        let history = &histories[page];
+        (history.times[k - 1], history.last)
+    };
+    let unpinned = || v_memory.iter().enumerate().filter(|&(_, page)| !pins.contains(*page));
+    unpinned()
+        .filter(|&(_, page)| time - histories[page].last > correlated_period)
+        .min_by_key(key)
+        .or_else(|| unpinned().min_by_key(|&(_, page)| histories[page].last))
+        .map(|(i, _)| i)
+        .unwrap()
+}
+
+// Drops the histories of evicted pages that were used least recently until we're within the bound
+fn forget_oldest(histories : &mut HashMap<usize, History> , v_memory : &[usize] , retained : usize) {
+    loop {
+        let non_resident : Vec<(usize, usize)> = histories.iter()
+            .filter(|&(page, _)| !v_memory.contains(page))
+            .map(|(&page, history)| (history.last, page))
+            .collect();
+        if non_resident.len() <= retained {
+            return;
+        }
+        let &(_, page) = non_resident.iter().min().unwrap();
+        histories.remove(&page);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use {get_total_faults, second};
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(2, 0, 4, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 W:1 R:1 W:1"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(2, 0, 4, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        for result in results.iter().skip(1) {
+            assert_eq!(*result , AccessResult::Hit);
+        }
+    }
+
+    #[test]
+    fn k_of_one_is_lru() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1 R:3"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(1, 0, 4, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[3] , AccessResult::MissReplace(MissReplacement::new(2, 1, 3)));
+    }
+
+    #[test]
+    fn pages_seen_once_go_before_pages_seen_twice() {
+        // 1 is the least recently used but it's the only one with two references
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 R:2 R:3 R:4"));
+        let total_pages = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(2, 0, 4, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(2, 1, 4)));
+    }
+
+    #[test]
+    fn correlated_references_only_count_once() {
+        // With a period of 1 the second R:1 is part of the same burst, so 1 has only been seen
+        // once and it's the least recently used. 3 is still in its period so it can't go either way.
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 R:2 R:3 R:4"));
+        let total_pages = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(2, 1, 4, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(1, 0, 4)));
+    }
+
+    #[test]
+    fn evicted_page_remembers_its_history() {
+        // 1 is seen once and evicted by 3, when it comes back it has two references and the older
+        // one is still more recent than 2's, so 2 goes
+        let accesses = MemoryAccess::create(String::from("R:2 R:2 R:1 R:3 R:1 R:4"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(2, 0, 4, total_pages, accesses.clone(), v_memory).unwrap();
+        assert_eq!(results[3] , AccessResult::MissReplace(MissReplacement::new(1, 1, 3)));
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(3, 1, 1)));
+        assert_eq!(results[5] , AccessResult::MissReplace(MissReplacement::new(2, 0, 4)));
+
+        // Without any history kept it's just another page seen once
+        let results = process_page_requests(2, 0, 0, total_pages, accesses, Vec::with_capacity(total_pages)).unwrap();
+        assert_eq!(results[5] , AccessResult::MissReplace(MissReplacement::new(1, 1, 4)));
+    }
+
+    #[test]
+    fn hot_pages_survive_a_scan_unlike_second_chance() {
+        let mut trace = String::from("R:1 R:2 R:1 R:2");
+        for page in 10..20 {
+            trace.push_str(&format!(" R:{}", page));
+        }
+        trace.push_str(" R:1 R:2");
+        let accesses = MemoryAccess::create(trace);
+        let lruk = process_page_requests(2, 0, 4, 3, accesses.clone(), Vec::with_capacity(3)).unwrap();
+        let second = second::process_page_requests(3, accesses, VecDeque::with_capacity(3)).unwrap();
+        assert_eq!(lruk[14] , AccessResult::Hit);
+        assert_eq!(lruk[15] , AccessResult::Hit);
+        assert_ne!(second[14] , AccessResult::Hit);
+        assert_ne!(second[15] , AccessResult::Hit);
+    }
+}
@@ -0,0 +1,271 @@
+// Multi-Generational LRU: every resident page belongs to a generation, numbered by a sequence that
+// only goes up. New pages join the youngest generation (max_seq) and eviction takes from the oldest
+// (min_seq). Hits only set the referenced bit, like the hardware accessed bit would.
+// An aging walk opens a new youngest generation and moves every page that was referenced since
+// the last walk into it. We walk whenever we're about to evict and there's room for another
+// generation, so more generations means finer grained recency.
+// Eviction takes the page that joined the oldest generation first. If it was referenced in the
+// meantime it gets promoted to the youngest generation instead, and dirty victims are written back.
+// Pinned pages in the oldest generation are moved along to the youngest one before picking.
+use {exit_on_error, parse_file, parse_number};
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+// What the comparison runs use when nobody tells us otherwise
+pub const DEFAULT_GENERATIONS : usize = 4;
+
+// MGLRU parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct MGLRU_Params {
+    pub total_frames : usize,
+    // Most generations alive at once, at least 2
+    pub generations : usize,
+    // Only used for the WSClock run we compare against
+    pub tau : usize,
+    pub access_string : String,
+}
+
+impl MGLRU_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> MGLRU_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let frames = exit_on_error(parse_number("frame count", args.get(1)));
+        let generations = exit_on_error(parse_number("generation count", args.get(2)).and_then(|g| {
+            if g >= 2 { Ok(g) } else { Err(String::from("The generation count has to be at least 2")) }
+        }));
+        let tau = exit_on_error(parse_number("tau", args.get(3)));
+        let file = exit_on_error(parse_file(args.get(4)));
+        MGLRU_Params {
+            total_frames : frames,
+            generations,
+            tau,
+            access_string : file,
+        }
+    }
+}
+
+pub fn process_page_requests(generations : usize , total_physical_pages : usize , accesses : Vec<MemoryAccess> , v_memory : Vec<Page>) -> Result<Vec<AccessResult>, String> {
+    Ok(process_page_requests_with_occupancy(generations, total_physical_pages, accesses, v_memory)?.0)
+}
+
+// Resident pages per generation after every access, oldest generation first
+pub type Occupancy = Vec<Vec<usize>>;
+
+// Same as above, but also hands back how many resident pages each generation had after every
+// access (oldest first) and every page that was written back, in order
+pub fn process_page_requests_with_occupancy(generations : usize , total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<Page>) -> Result<(Vec<AccessResult>, Occupancy, Vec<usize>), String> {
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut occupancy : Vec<Vec<usize>> = Vec::with_capacity(accesses.len());
+    let mut lru = Generations {
+        min_seq : 0,
+        max_seq : 1,
+        generations,
+        stamp : 0,
+        writes : Vec::new(),
+    };
+    let mut pins = Pins::new();
+
+    for access in accesses.iter() {
+        pins.update(access);
+        let x = access.frame_number;
+        let is_write = access.access_type == AccessType::Write;
+        if access.access_type == AccessType::Free {
+            // Gone from its generation without a write back
+            v_memory.retain(|p| p.number != x);
+            results.push(AccessResult::Freed);
+        } else if let Some(page) = v_memory.iter_mut().find(|p| p.number == x) {
+            page.referenced = true;
+            page.is_dirty |= is_write;
+            results.push(AccessResult::Hit);
+        } else {
+            let page = Page { number : x, gen : lru.max_seq, referenced : false, is_dirty : is_write, stamp : lru.next_stamp() };
+            if v_memory.len() < total_physical_pages {
+                v_memory.push(page);
+                results.push(AccessResult::MissSimple);
+            } else {
+                pins.check_evictable(total_physical_pages, x)?;
+                let index = lru.evict(&mut v_memory, &pins);
+                results.push(AccessResult::MissReplace(MissReplacement::new(v_memory[index].number, index, x)));
+                // The walk may have opened a new generation, so it joins whatever's youngest now
+                v_memory[index] = Page { gen : lru.max_seq, ..page };
+            }
+        }
+        occupancy.push(lru.occupancy(&v_memory));
+    }
+
+    Ok((results, occupancy, lru.writes))
+}
+
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub number : usize,
+    // Sequence number of the generation it's in
+    pub gen : usize,
+    pub referenced : bool,
+    pub is_dirty : bool,
+    // When it joined its generation, the first to join is the first to go
+    pub stamp : usize,
+}
+
+// The sequence numbers of the oldest and youngest generations
+#[derive(Debug, Clone)]
+pub struct Generations {
+    pub min_seq : usize,
+    pub max_seq : usize,
+    pub generations : usize,
+    pub stamp : usize,
+    pub writes : Vec<usize>,
+}
+
+impl Generations {
+    fn next_stamp(&mut self) -> usize {
+        self.stamp += 1;
+        self.stamp
+    }
+
+    fn count(&self) -> usize {
+        self.max_seq - self.min_seq + 1
+    }
+
+    // The aging walk, opens a new youngest generation and moves the referenced pages into it
+    fn age(&mut self , v_memory : &mut [Page]) {
+        self.max_seq += 1;
+        for page in v_memory.iter_mut().filter(|p| p.referenced) {
+            page.referenced = false;
+            page.gen = self.max_seq;
+            page.stamp = self.next_stamp();
+        }
+    }
+
+    // Finds a page to evict in the oldest generation and gives back its frame
+    fn evict(&mut self , v_memory : &mut [Page] , pins : &Pins) -> usize {
+        loop {
+            // Generations that emptied out are done with
+            while self.min_seq < self.max_seq && !v_memory.iter().any(|p| p.gen == self.min_seq) {
+                self.min_seq += 1;
+            }
+            if self.count() < self.generations {
+                self.age(v_memory);
+                if !v_memory.iter().any(|p| p.gen == self.min_seq) {
+                    continue;
+                }
+            }
+            let min_seq = self.min_seq;
+            let pinned : Vec<usize> = (0..v_memory.len())
+                .filter(|&i| v_memory[i].gen == min_seq && pins.contains(v_memory[i].number))
+                .collect();
+            for i in pinned {
+                v_memory[i].gen = self.max_seq;
+                v_memory[i].stamp = self.next_stamp();
+            }
+            if !v_memory.iter().any(|p| p.gen == min_seq) {
+                continue;
+            }
+            let index = v_memory.iter()
+                .enumerate()
+                .filter(|&(_, p)| p.gen == min_seq)
+                .min_by_key(|&(_, p)| p.stamp)
+                .map(|(i, _)| i)
+                .unwrap();
+            if v_memory[index].referenced {
+                // Used since the last walk, it shouldn't be in the oldest generation anymore
+                v_memory[index].referenced = false;
+                v_memory[index].gen = self.max_seq;
+                v_memory[index].stamp = self.next_stamp();
+                continue;
+            }
+            if v_memory[index].is_dirty {
+                self.writes.push(v_memory[index].number);
+            }
+            return index;
+        }
+    }
+
+    // Resident pages per generation, oldest first
+    fn occupancy(&self , v_memory : &[Page]) -> Vec<usize> {
+        (self.min_seq..=self.max_seq)
+            .map(|seq| v_memory.iter().filter(|p| p.gen == seq).count())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use {get_total_faults, second};
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(4, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 W:1 R:1 W:1"));
+        let total_pages = 2;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(4, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        for result in results.iter().skip(1) {
+            assert_eq!(*result , AccessResult::Hit);
+        }
+    }
+
+    #[test]
+    fn walk_moves_referenced_pages_to_a_new_generation() {
+        // 1 is referenced, so the walk before evicting puts it in generation 2 and 2 goes
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1 R:3"));
+        let total_pages = 2;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let (results, occupancy, writes) = process_page_requests_with_occupancy(4, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[3] , AccessResult::MissReplace(MissReplacement::new(2, 1, 3)));
+        assert_eq!(occupancy[2] , vec![0, 2]);
+        // Generation 1 is empty now, 1 and 3 are both in 2
+        assert_eq!(occupancy[3] , vec![0, 2]);
+        assert!(writes.is_empty());
+    }
+
+    #[test]
+    fn referenced_page_in_the_oldest_generation_is_promoted_instead() {
+        // Only 2 generations, so no walk happens while 2 and 3 are in different ones. 2 is the
+        // oldest when 4 comes along but it was referenced, so it moves up and 3 goes.
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:2 R:4"));
+        let total_pages = 2;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(2, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[2] , AccessResult::MissReplace(MissReplacement::new(1, 0, 3)));
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(3, 0, 4)));
+    }
+
+    #[test]
+    fn dirty_victims_are_written_back() {
+        let accesses = MemoryAccess::create(String::from("W:1 R:2 R:3 R:4"));
+        let total_pages = 2;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let (results, _, writes) = process_page_requests_with_occupancy(4, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[2] , AccessResult::MissReplace(MissReplacement::new(1, 0, 3)));
+        assert_eq!(writes , vec![1]);
+    }
+
+    #[test]
+    fn keeps_a_hot_page_that_second_chance_loses_to_a_scan() {
+        let mut trace : Vec<String> = Vec::new();
+        for page in 10..30 {
+            trace.push(String::from("R:1"));
+            trace.push(format!("R:{}", page));
+        }
+        let accesses = MemoryAccess::create(trace.join(" "));
+        let mglru = process_page_requests(4, 3, accesses.clone(), Vec::with_capacity(3)).unwrap();
+        let second = second::process_page_requests(3, accesses, VecDeque::with_capacity(3)).unwrap();
+        assert!(get_total_faults(&mglru) <= get_total_faults(&second));
+        assert_eq!(mglru[38] , AccessResult::Hit);
+    }
+}
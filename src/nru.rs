@@ -0,0 +1,169 @@
+// Not Recently Used: every page falls into one of four classes by its R and M bits and a random
+// page from the lowest non-empty class is evicted. R bits get cleared every so often so that
+// "recently" actually means something.
+use random::Random;
+use {exit_on_error, parse_file, parse_nonzero, parse_number};
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+// What the comparison runs use when nobody tells us otherwise
+pub const DEFAULT_INTERVAL : usize = 4;
+pub const DEFAULT_SEED : u64 = 4017;
+
+// NRU parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct NRU_Params {
+    pub total_frames : usize,
+    // Clear every R bit after this many accesses
+    pub interval : usize,
+    pub seed : u64,
+    pub access_string : String,
+}
+
+impl NRU_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> NRU_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let frames = exit_on_error(parse_number("frame count", args.get(1)));
+        let interval = exit_on_error(parse_nonzero("interval", args.get(2)));
+        let seed = exit_on_error(parse_number("seed", args.get(3)));
+        let file = exit_on_error(parse_file(args.get(4)));
+        NRU_Params {
+            total_frames : frames,
+            interval,
+            seed : seed as u64,
+            access_string : file,
+        }
+    }
+}
+
+pub fn process_page_requests(interval : usize , seed : u64 , total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<Page>) -> Result<Vec<AccessResult>, String> {
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut random = Random::new(seed);
+    let mut pins = Pins::new();
+
+    for (clock, access) in accesses.iter().enumerate() {
+        pins.update(access);
+        let is_write = access.access_type == AccessType::Write;
+        // A freed page gives its frame up, the periodic clear still happens
+        if access.access_type == AccessType::Free {
+            v_memory.retain(|page| page.number != access.frame_number);
+            results.push(AccessResult::Freed);
+        } else if let Some(index) = contains_page(access.frame_number, &v_memory) {
+            v_memory[index].referenced = true;
+            if is_write {
+                v_memory[index].is_dirty = true;
+            }
+            results.push(AccessResult::Hit);
+        } else if v_memory.len() < total_physical_pages {
+            // We have space, so it's a simple miss
+            v_memory.push(Page { number : access.frame_number, referenced : true, is_dirty : is_write });
+            results.push(AccessResult::MissSimple);
+        } else {
+            // Gather up every unpinned frame in the lowest class and pick one of them at random
+            pins.check_evictable(total_physical_pages, access.frame_number)?;
+            let lowest = v_memory.iter().filter(|p| !pins.contains(p.number)).map(|p| p.class()).min().unwrap();
+            let candidates : Vec<usize> = v_memory.iter()
+                .enumerate()
+                .filter(|&(_, p)| p.class() == lowest && !pins.contains(p.number))
+                .map(|(i, _)| i)
+                .collect();
+            let index = candidates[random.below(candidates.len())];
+            results.push(AccessResult::MissReplace(
+                MissReplacement::new(
+                    v_memory[index].number,
+                    index,
+                    access.frame_number)));
+            v_memory[index] = Page { number : access.frame_number, referenced : true, is_dirty : is_write };
+        }
+
+        // Periodic clear, M bits stay because the page still needs writing out
+        if (clock + 1) % interval == 0 {
+            for page in v_memory.iter_mut() {
+                page.referenced = false;
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+fn contains_page(page_num : usize , collection : &[Page]) -> Option<usize> {
+    collection.iter().position(|item| item.number == page_num)
+}
+
+// WSClock's page without the timestamp
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub number : usize,
+    pub referenced : bool,
+    pub is_dirty : bool,
+}
+
+impl Page {
+    // 0: not referenced, clean
+    // 1: not referenced, dirty
+    // 2: referenced, clean
+    // 3: referenced, dirty
+    pub fn class(&self) -> u8 {
+        (self.referenced as u8) * 2 + self.is_dirty as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(4, 1, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn classes_follow_r_and_m_bits() {
+        let page = |referenced, is_dirty| Page { number : 0, referenced, is_dirty };
+        assert_eq!(page(false, false).class() , 0);
+        assert_eq!(page(false, true).class() , 1);
+        assert_eq!(page(true, false).class() , 2);
+        assert_eq!(page(true, true).class() , 3);
+    }
+
+    #[test]
+    fn evicts_clean_unreferenced_page_over_dirty_one() {
+        // Interval 3 clears R after W:1 R:2 W:3, so 1 and 3 are class 1 and 2 is class 0
+        let accesses = MemoryAccess::create(String::from("W:1 R:2 W:3 R:4"));
+        let total_pages = 3;
+        for seed in 0..10 {
+            let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+            let results = process_page_requests(3, seed, total_pages, accesses.clone(), v_memory).unwrap();
+            let mr = MissReplacement::new(2, 1, 4);
+            assert_eq!(results[3] , AccessResult::MissReplace(mr));
+        }
+    }
+
+    #[test]
+    fn evicts_unreferenced_dirty_page_over_referenced_clean_one() {
+        // After the clear, R:2 is touched again so it's class 2 while 1 is still class 1
+        let accesses = MemoryAccess::create(String::from("W:1 R:2 R:2 R:3"));
+        let total_pages = 2;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(2, 1, total_pages, accesses, v_memory).unwrap();
+        let mr = MissReplacement::new(1, 0, 3);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+    }
+
+    #[test]
+    fn same_seed_gives_same_victims() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:5 R:6 R:1 R:2 R:7 R:8 R:3"));
+        let run = |seed| process_page_requests(100, seed, 3, accesses.clone(), Vec::with_capacity(3)).unwrap();
+        assert_eq!(run(9) , run(9));
+    }
+}
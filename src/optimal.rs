@@ -0,0 +1,269 @@
+// Optimal: evicts the page whose next access lies furthest in the future
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+pub fn process_page_requests(total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut pages : Vec<usize>) -> Result<Vec<AccessResult>, String> {
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut pins = Pins::new();
+    // Iterate over all the accesses in order
+    for (i, access) in accesses.iter().enumerate() {
+        pins.update(access);
+        // A freed page just leaves its frame empty
+        if access.access_type == AccessType::Free {
+            pages.retain(|&p| p != access.frame_number);
+            results.push(AccessResult::Freed);
+            continue;
+        }
+        // Does the page exist?
+        if !pages.contains(&access.frame_number) {
+            let length = pages.len();
+            // Crucially, here we check if we have space, if we do, it's a simple miss
+            if length < total_physical_pages {
+                pages.push(access.frame_number);
+                results.push(AccessResult::MissSimple);
+            } else {
+                pins.check_evictable(total_physical_pages, access.frame_number)?;
+                // Here we have nested loops that will go through all of the existing pages
+                let mut index = 0;
+                // max here keeps track of the highest index of a memory access, so that the
+                // page that is accessed the latest is the one we remove
+                let mut max : Option<usize> = None;
+                for (ii, vm) in pages.iter().enumerate() {
+                    // Pinned pages can't go no matter how far off their next access is
+                    if pins.contains(*vm) {
+                        continue;
+                    }
+                    let mut was_found = false;
+                    // Only iterate from the last memory access till the end, because it doesn't
+                    // make sense to start from the beginning
+                    for (jj, acc) in accesses.iter().enumerate().skip(i + 1) {
+                        if vm == &acc.frame_number {
+                            // Freed before it's used again, so it's as good as never used again
+                            if acc.access_type == AccessType::Free {
+                                break;
+                            }
+                            // The frame is accessed later on in the memory accesses!
+                            was_found = true;
+                            if max.is_none() || max.unwrap() < jj {
+                                index = ii;
+                                max = Some(jj);
+                            }
+                            break;
+                        }
+                    }
+                    // After we initially found a page that is accessed, we later found one that
+                    // wasn't
+                    if !was_found && max.is_some() {
+                        max = None;
+                    }
+                    // None means that the page is no longer accessed, so we can remove it
+                    match max  {
+                        Some(_) => (),
+                        None => {
+                            index = ii;
+                            break;
+                        }
+                    }
+                }
+                // Finally! handle the replacement
+                results.push(AccessResult::MissReplace(
+                    MissReplacement::new(
+                        pages[index],
+                        index,
+                        access.frame_number)));
+                pages[index] = access.frame_number;
+            }
+        } else {
+            results.push(AccessResult::Hit);
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_frames = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_frames);
+        let results = process_page_requests(total_frames, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn third_miss_is_miss_replace() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_frames = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_frames);
+        let results = process_page_requests(total_frames, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+
+        // Replaced R:1 at index 0 with R:3
+        let mr = MissReplacement::new(1, 0, 3);
+        assert_eq!(results[2] , AccessResult::MissReplace(mr));
+
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 W:1 R:1 W:1"));
+        let total_frames = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_frames);
+        let results = process_page_requests(total_frames, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        for result in results.iter().skip(1) {
+            assert_eq!(*result , AccessResult::Hit);
+        }
+        assert_eq!(get_total_faults(&results) , 1);
+    }
+
+    #[test]
+    fn alternating_hits_and_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 W:1 W:2 R:1 R:2 W:3 W:4"));
+        let total_frames = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_frames);
+        let results = process_page_requests(total_frames, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::Hit);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(results[3] , AccessResult::Hit);
+        assert_eq!(results[4] , AccessResult::Hit);
+        assert_eq!(results[5] , AccessResult::MissSimple);
+        assert_eq!(results[6] , AccessResult::MissSimple);
+        assert_eq!(results.len() , 7);
+        assert_eq!(get_total_faults(&results) , 4);
+    }
+
+    #[test]
+    fn replace_the_last_one_to_be_accessed() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:1 R:2 R:3"));
+        let total_frames = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_frames);
+        let results = process_page_requests(total_frames, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(results[4] , AccessResult::Hit);
+        assert_eq!(results[5] , AccessResult::Hit);
+
+        // Replaced R:3 at index 2 with R:4
+        let mr = MissReplacement::new(3, 2, 4);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+
+        // Replaced R:1 at index 0 with R:3
+        let mr = MissReplacement::new(1, 0, 3);
+        assert_eq!(results[6] , AccessResult::MissReplace(mr));
+
+        assert_eq!(get_total_faults(&results) , 5);
+    }
+
+    #[test]
+    fn replace_second_because_its_no_longer_accessed() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:1 R:4"));
+        let total_frames = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_frames);
+        let results = process_page_requests(total_frames, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(results[4] , AccessResult::Hit);
+        assert_eq!(results[5] , AccessResult::Hit);
+
+        // Replaced R:2 at index 1 with R:4
+        let mr = MissReplacement::new(2, 1, 4);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+
+        assert_eq!(get_total_faults(&results) , 4);
+    }
+
+    #[test]
+    fn replace_first_and_third_because_they_are_no_longer_accessed() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:5 R:2 R:4"));
+        let total_frames = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_frames);
+        let results = process_page_requests(total_frames, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+
+        // Replaced R:1 at index 0 with R:4
+        let mr = MissReplacement::new(1, 0, 4);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+
+        // Replaced R:3 at index 2 with R:5
+        let mr = MissReplacement::new(3, 2, 5);
+        assert_eq!(results[4] , AccessResult::MissReplace(mr));
+
+        assert_eq!(get_total_faults(&results) , 5);
+    }
+
+    #[test]
+    fn replace_first_twice_because_of_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4"));
+        let total_frames = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_frames);
+        let results = process_page_requests(total_frames, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+
+        // Replaced R:1 at index 0 with R:3
+        let mr = MissReplacement::new(1, 0, 3);
+        assert_eq!(results[2] , AccessResult::MissReplace(mr));
+
+        // Replaced R:3 at index 0 with R:4
+        let mr = MissReplacement::new(3, 0, 4);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+
+        assert_eq!(get_total_faults(&results) , 4);
+    }
+
+    #[test]
+    fn replace_first_thrice() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:5 R:3 R:2 R:1"));
+        let total_frames = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_frames);
+        let results = process_page_requests(total_frames, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(results[5] , AccessResult::Hit);
+        assert_eq!(results[6] , AccessResult::Hit);
+
+        // Replaced R:3 at index 2 with R:4
+        let mr = MissReplacement::new(1, 0, 4);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+
+        // Replaced R:4 at index 0 with R:5
+        let mr = MissReplacement::new(4, 0, 5);
+        assert_eq!(results[4] , AccessResult::MissReplace(mr));
+
+        // Replaced R:5 at index 0 with R:1
+        let mr = MissReplacement::new(5, 0, 1);
+        assert_eq!(results[7] , AccessResult::MissReplace(mr));
+
+        assert_eq!(get_total_faults(&results) , 6);
+    }
+
+    #[test]
+    fn freed_page_goes_first_and_its_frame_fills_again() {
+        // 1 is used again but only after it's been freed, so it's the one to go for 4
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:2 R:3 F:1 R:1 F:2-3 R:5"));
+        let results = process_page_requests(3, accesses, Vec::with_capacity(3)).unwrap();
+        assert_eq!(results[3] , AccessResult::MissReplace(MissReplacement::new(1, 0, 4)));
+        assert_eq!(results[6] , AccessResult::Freed);
+        assert_eq!(results[7] , AccessResult::MissReplace(MissReplacement::new(4, 0, 1)));
+        assert_eq!(results[8] , AccessResult::Freed);
+        assert_eq!(results[9] , AccessResult::Freed);
+        assert_eq!(results[10] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 6);
+    }
+
+}
@@ -0,0 +1,343 @@
+// A hierarchical page table on top of the trace. The page numbers in the trace are virtual page
+// numbers, split into one index per level, top level first (with 10,10 page 0x1403 is entry 5 of
+// the root and entry 3 of the second level table that entry points at). Every TLB miss walks the
+// table from the root, one memory reference per level. Tables are allocated the first time
+// something they map is touched and never freed.
+// Normally the tables sit in memory of their own. When they're paged, every walk becomes extra
+// accesses to the table pages in the trace we hand the algorithm, so they compete for the same
+// frames as the data and can be evicted and faulted back in like any other page. The TLB only ever
+// holds data pages, but it's replayed against that run, so a data page pushed out by a table page
+// is shot down like any other.
+use std::collections::HashMap;
+use compare::Algorithm;
+use tlb::{simulate, Tlb, TlbAccess, TlbPolicy};
+use {exit_on_error, get_total_faults, parse_file, parse_number};
+use {AccessResult, AccessType, MemoryAccess};
+
+// Bytes per page table entry
+pub const ENTRY_BYTES : usize = 8;
+
+// Page table parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct PAGETABLE_Params {
+    pub algorithm : String,
+    pub total_frames : usize,
+    pub tau : usize,
+    // Index bits per level, top level first
+    pub bits : Vec<u32>,
+    // Fully associative LRU TLB, 0 for none at all
+    pub tlb_entries : usize,
+    // Whether the tables take up frames and get replaced like data pages
+    pub paged : bool,
+    pub access_string : String,
+}
+
+impl PAGETABLE_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> PAGETABLE_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let algorithm = exit_on_error(args.get(1).cloned().ok_or_else(|| String::from("No algorithm provided")));
+        let frames = exit_on_error(parse_number("frame count", args.get(2)));
+        let tau = exit_on_error(parse_number("tau", args.get(3)));
+        let bits = exit_on_error(parse_bits(args.get(4)));
+        let tlb_entries = exit_on_error(parse_number("TLB entry count", args.get(5)));
+        let paged = exit_on_error(match args.get(6).map(|s| s.as_str()) {
+            Some("paged") => Ok(true),
+            Some("pinned") => Ok(false),
+            Some(t) => Err(format!("Tables must be paged or pinned, not {}", t)),
+            None => Err(String::from("No table mode provided")),
+        });
+        let file = exit_on_error(parse_file(args.get(7)));
+        PAGETABLE_Params {
+            algorithm,
+            total_frames : frames,
+            tau,
+            bits,
+            tlb_entries,
+            paged,
+            access_string : file,
+        }
+    }
+}
+
+// Bits per level separated by commas, like 9,9,9,9 for x86-64's four levels
+pub fn parse_bits(bits_string : Option<&String>) -> Result<Vec<u32>, String> {
+    let bits_string = bits_string.ok_or_else(|| String::from("No bits per level provided"))?;
+    let bits : Vec<u32> = bits_string.split(',')
+        .map(|b| match b.parse::<u32>() {
+            Ok(n) if n >= 1 => Ok(n),
+            _ => Err(format!("Invalid bits per level {}", bits_string)),
+        })
+        .collect::<Result<_, _>>()?;
+    if bits.iter().sum::<u32>() >= usize::BITS {
+        return Err(String::from("The levels can't have more bits than a page number"));
+    }
+    Ok(bits)
+}
+
+// A table is known by its level (0 is the root) and the page number bits above it
+pub type Table = (usize, usize);
+
+#[derive(Debug, Clone)]
+pub struct PageTable {
+    pub bits : Vec<u32>,
+}
+
+impl PageTable {
+    pub fn new(bits : Vec<u32>) -> PageTable {
+        PageTable { bits }
+    }
+
+    pub fn levels(&self) -> usize {
+        self.bits.len()
+    }
+
+    // Every page number has to fit in the bits of all the levels together
+    pub fn check(&self , accesses : &[MemoryAccess]) -> Result<(), String> {
+        let total : u32 = self.bits.iter().sum();
+        match accesses.iter().find(|a| a.frame_number >> total != 0) {
+            Some(a) => Err(format!("Page {} doesn't fit in {} bits of page number", a.frame_number, total)),
+            None => Ok(()),
+        }
+    }
+
+    // The tables a walk for this page goes through, root first
+    pub fn walk(&self , page : usize) -> Vec<Table> {
+        let mut below : u32 = self.bits.iter().sum();
+        self.bits.iter().enumerate().map(|(level, bits)| {
+            let table = (level, page >> below);
+            below -= bits;
+            table
+        }).collect()
+    }
+
+    // Every table the trace needs, in the order they're first touched
+    pub fn tables(&self , accesses : &[MemoryAccess]) -> Vec<Table> {
+        let mut tables : Vec<Table> = Vec::new();
+        for access in accesses {
+            for table in self.walk(access.frame_number) {
+                if !tables.contains(&table) {
+                    tables.push(table);
+                }
+            }
+        }
+        tables
+    }
+
+    // What the tables take up, every one of them has an entry for each index at its level
+    pub fn overhead_bytes(&self , tables : &[Table]) -> usize {
+        tables.iter().map(|&(level, _)| (1usize << self.bits[level]) * ENTRY_BYTES).sum()
+    }
+}
+
+// What happened when running the algorithm with the table in place
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalkReport {
+    // The results for the data accesses only
+    pub results : Vec<AccessResult>,
+    pub tlb_accesses : Vec<TlbAccess>,
+    pub table_pages : usize,
+    pub overhead_bytes : usize,
+    pub walks : usize,
+    pub walk_references : usize,
+    // Faults on table pages, always 0 unless they're paged
+    pub table_faults : usize,
+}
+
+// TLB hits and misses for the data pages, every access misses without a TLB
+pub fn tlb_accesses(tlb_entries : usize , accesses : &[MemoryAccess] , results : &[AccessResult]) -> Vec<TlbAccess> {
+    if tlb_entries == 0 {
+        return vec![TlbAccess { hit : false, shootdown : None }; accesses.len()];
+    }
+    let mut tlb = Tlb::new(tlb_entries, tlb_entries, TlbPolicy::Lru);
+    simulate(&mut tlb, accesses, results)
+}
+
+// Table pages get page numbers past the biggest data page so they can't be mistaken for one
+pub fn table_page_numbers(tables : &[Table] , accesses : &[MemoryAccess]) -> HashMap<Table, usize> {
+    let first = accesses.iter().map(|a| a.frame_number).max().map_or(0, |m| m + 1);
+    tables.iter().enumerate().map(|(i, &table)| (table, first + i)).collect()
+}
+
+// The TLB over the data accesses of a paged run, data_indexes saying where they are in its trace.
+// A data page that a table read pushes out is shot down on the access whose walk did it.
+fn paged_tlb_accesses(tlb_entries : usize , trace : &[MemoryAccess] , all_results : &[AccessResult] , data_indexes : &[usize]) -> Vec<TlbAccess> {
+    if tlb_entries == 0 {
+        return vec![TlbAccess { hit : false, shootdown : None }; data_indexes.len()];
+    }
+    let mut tlb = Tlb::new(tlb_entries, tlb_entries, TlbPolicy::Lru);
+    let mut walk_start = 0;
+    data_indexes.iter().enumerate().map(|(time, &i)| {
+        let mut shootdown = None;
+        for result in &all_results[walk_start..i] {
+            if let AccessResult::MissReplace(ref mr) = *result {
+                if tlb.invalidate(mr.replaced) {
+                    shootdown = Some(mr.replaced);
+                }
+            }
+        }
+        walk_start = i + 1;
+        // From here on the same as tlb::simulate
+        let page = trace[i].frame_number;
+        if all_results[i] == AccessResult::Freed {
+            if tlb.invalidate(page) {
+                shootdown = Some(page);
+            }
+            return TlbAccess { hit : false, shootdown };
+        }
+        if tlb.lookup(page, time) {
+            return TlbAccess { hit : true, shootdown };
+        }
+        if let AccessResult::MissReplace(ref mr) = all_results[i] {
+            if tlb.invalidate(mr.replaced) {
+                shootdown = Some(mr.replaced);
+            }
+        }
+        tlb.insert(page, time);
+        TlbAccess { hit : false, shootdown }
+    }).collect()
+}
+
+// A free doesn't walk the tables, the TLB only shoots its entry down
+fn walks_of(accesses : &[MemoryAccess] , tlb_accesses : &[TlbAccess]) -> Vec<bool> {
+    accesses.iter().zip(tlb_accesses.iter())
+        .map(|(access, tlb)| !tlb.hit && access.access_type != AccessType::Free)
+        .collect()
+}
+
+// Every walk reads the tables before the data access itself. Gives back the trace and where the
+// data accesses ended up in it.
+fn paged_trace(page_table : &PageTable , numbers : &HashMap<Table, usize> , accesses : &[MemoryAccess] , walks_at : &[bool]) -> (Vec<MemoryAccess>, Vec<usize>) {
+    let mut trace : Vec<MemoryAccess> = Vec::new();
+    let mut data_indexes : Vec<usize> = Vec::with_capacity(accesses.len());
+    for (access, &walk) in accesses.iter().zip(walks_at.iter()) {
+        if walk {
+            for table in page_table.walk(access.frame_number) {
+                trace.push(MemoryAccess { frame_number : numbers[&table], access_type : AccessType::Read });
+            }
+        }
+        data_indexes.push(trace.len());
+        trace.push(access.clone());
+    }
+    (trace, data_indexes)
+}
+
+pub fn run(algorithm : &Algorithm , page_table : &PageTable , tlb_entries : usize , paged : bool , total_frames : usize , accesses : &[MemoryAccess]) -> Result<WalkReport, String> {
+    let tables = page_table.tables(accesses);
+    let data_results = (algorithm.run)(total_frames, accesses.to_vec())?;
+    let mut tlb_accesses = tlb_accesses(tlb_entries, accesses, &data_results);
+    let mut walks_at = walks_of(accesses, &tlb_accesses);
+
+    let (results, table_faults) = if paged {
+        // The walks depend on the TLB, the TLB on what the paged run evicts and that on the walks.
+        // Start from the TLB of the run without tables, and wherever the paged run's own TLB first
+        // walks differently, take its walks from there on and go again. Everything before that
+        // point runs the same, so every round gets further along the trace.
+        let numbers = table_page_numbers(&tables, accesses);
+        let mut from = 0;
+        loop {
+            let (trace, data_indexes) = paged_trace(page_table, &numbers, accesses, &walks_at);
+            let all_results = (algorithm.run)(total_frames, trace.clone())?;
+            tlb_accesses = paged_tlb_accesses(tlb_entries, &trace, &all_results, &data_indexes);
+            let paged_walks_at = walks_of(accesses, &tlb_accesses);
+            match (from..accesses.len()).find(|&i| paged_walks_at[i] != walks_at[i]) {
+                Some(i) => {
+                    walks_at[i..].copy_from_slice(&paged_walks_at[i..]);
+                    from = i + 1;
+                }
+                None => {
+                    let results : Vec<AccessResult> = data_indexes.iter().map(|&i| all_results[i].clone()).collect();
+                    let table_faults = get_total_faults(&all_results) - get_total_faults(&results);
+                    break (results, table_faults);
+                }
+            }
+        }
+    } else {
+        (data_results, 0)
+    };
+    let walks = walks_at.iter().filter(|&&walk| walk).count();
+
+    Ok(WalkReport {
+        results,
+        tlb_accesses,
+        table_pages : tables.len(),
+        overhead_bytes : page_table.overhead_bytes(&tables),
+        walks,
+        walk_references : walks * page_table.levels(),
+        table_faults,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compare::algorithm;
+
+    #[test]
+    fn walk_splits_the_page_number_per_level() {
+        let page_table = PageTable::new(vec![2, 4, 4]);
+        assert_eq!(page_table.walk(0x3a5) , vec![(0, 0), (1, 0x3), (2, 0x3a)]);
+        let accesses = MemoryAccess::create(String::from("R:1023"));
+        assert!(page_table.check(&accesses).is_ok());
+        let accesses = MemoryAccess::create(String::from("R:1024"));
+        assert_eq!(page_table.check(&accesses) , Err(String::from("Page 1024 doesn't fit in 10 bits of page number")));
+    }
+
+    #[test]
+    fn tables_and_their_overhead_are_counted_once() {
+        // 1 and 2 share every table, 300 needs its own second level table
+        let page_table = PageTable::new(vec![2, 8]);
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:300 R:1"));
+        let tables = page_table.tables(&accesses);
+        assert_eq!(tables , vec![(0, 0), (1, 0), (1, 1)]);
+        assert_eq!(page_table.overhead_bytes(&tables) , (4 + 256 + 256) * ENTRY_BYTES);
+    }
+
+    #[test]
+    fn every_tlb_miss_walks_every_level() {
+        let page_table = PageTable::new(vec![4, 4, 4]);
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1 R:2 R:3"));
+        let second = algorithm("second", 0).unwrap();
+        let report = run(&second, &page_table, 2, false, 4, &accesses).unwrap();
+        // 1 and 2 stay in the TLB, 3 pushes 1 out of it
+        assert_eq!(report.walks , 3);
+        assert_eq!(report.walk_references , 9);
+        assert_eq!(report.table_faults , 0);
+
+        let report = run(&second, &page_table, 0, false, 4, &accesses).unwrap();
+        assert_eq!(report.walks , 5);
+    }
+
+    #[test]
+    fn paged_tables_take_frames_from_the_data() {
+        let page_table = PageTable::new(vec![2, 2]);
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1 R:2"));
+        let second = algorithm("second", 0).unwrap();
+
+        // 2 frames are plenty for the data on its own
+        let pinned = run(&second, &page_table, 0, false, 2, &accesses).unwrap();
+        assert_eq!(get_total_faults(&pinned.results) , 2);
+
+        // With the root and one second level table walked before every access they don't fit
+        let paged = run(&second, &page_table, 0, true, 2, &accesses).unwrap();
+        assert_eq!(paged.results.len() , 4);
+        assert!(get_total_faults(&paged.results) > 2);
+        assert!(paged.table_faults >= 2);
+    }
+
+    #[test]
+    fn paged_evictions_shoot_down_the_tlb() {
+        let page_table = PageTable::new(vec![2, 2]);
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1 R:2 R:1"));
+        let second = algorithm("second", 0).unwrap();
+        // The TLB holds both data pages, but the tables keep pushing them out of memory, and a page
+        // that has to be faulted back in can't have been a TLB hit
+        let paged = run(&second, &page_table, 4, true, 2, &accesses).unwrap();
+        for (result, tlb) in paged.results.iter().zip(paged.tlb_accesses.iter()) {
+            assert!(!(result.is_fault() && tlb.hit));
+        }
+        assert!(paged.walks > 2);
+        assert!(paged.tlb_accesses.iter().any(|t| t.shootdown.is_some()));
+    }
+}
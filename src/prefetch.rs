@@ -0,0 +1,299 @@
+// Prefetching on top of demand paging. A prefetcher watches the demand accesses and after each one
+// names pages it thinks are coming up, and those are read in right after it, so the algorithm
+// underneath sees them as accesses of their own and they take up frames like anything else.
+//   Sequential: readahead, once accesses go page after page the window ahead of them doubles on
+//   every sequential access up to degree pages, and any jump closes it again
+//   Stride: once two accesses in a row move by the same stride, prefetches degree strides ahead
+//   Markov: remembers which pages followed which, and prefetches the degree most common
+//   successors of the current page
+// The prefetchers only see the demand accesses, not what's resident, so they may ask for a page
+// that's already there. That doesn't cost a read but it does count as a use for the algorithm.
+// A demand access to a prefetched page that hasn't been used yet is a PrefetchHit.
+use std::collections::{HashMap, HashSet};
+use compare::Algorithm;
+use {exit_on_error, get_total_faults, parse_file, parse_nonzero, parse_number};
+use {AccessResult, AccessType, MemoryAccess};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prefetcher {
+    Sequential,
+    Stride,
+    Markov,
+}
+
+pub fn parse_prefetcher(prefetcher_string : Option<&String>) -> Result<Prefetcher, String> {
+    match prefetcher_string.map(|s| s.as_str()) {
+        None => Err(String::from("No prefetcher provided")),
+        Some("sequential") => Ok(Prefetcher::Sequential),
+        Some("stride") => Ok(Prefetcher::Stride),
+        Some("markov") => Ok(Prefetcher::Markov),
+        Some(p) => Err(format!("Prefetcher must be sequential, stride or markov, not {}", p)),
+    }
+}
+
+// Prefetch parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct PREFETCH_Params {
+    pub algorithm : String,
+    pub total_frames : usize,
+    pub tau : usize,
+    pub prefetcher : Prefetcher,
+    // Most pages prefetched after one access
+    pub degree : usize,
+    pub access_string : String,
+}
+
+impl PREFETCH_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> PREFETCH_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let algorithm = exit_on_error(args.get(1).cloned().ok_or_else(|| String::from("No algorithm provided")));
+        let frames = exit_on_error(parse_number("frame count", args.get(2)));
+        let tau = exit_on_error(parse_number("tau", args.get(3)));
+        let prefetcher = exit_on_error(parse_prefetcher(args.get(4)));
+        let degree = exit_on_error(parse_nonzero("prefetch degree", args.get(5)));
+        let file = exit_on_error(parse_file(args.get(6)));
+        PREFETCH_Params {
+            algorithm,
+            total_frames : frames,
+            tau,
+            prefetcher,
+            degree,
+            access_string : file,
+        }
+    }
+}
+
+// The pages to prefetch after every demand access, the prefetchers don't see frees and never
+// prefetch after one
+pub fn prefetches(prefetcher : Prefetcher , degree : usize , accesses : &[MemoryAccess]) -> Vec<Vec<usize>> {
+    let demand : Vec<MemoryAccess> = accesses.iter()
+        .filter(|a| a.access_type != AccessType::Free)
+        .cloned()
+        .collect();
+    let mut prefetched = match prefetcher {
+        Prefetcher::Sequential => sequential(degree, &demand),
+        Prefetcher::Stride => stride(degree, &demand),
+        Prefetcher::Markov => markov(degree, &demand),
+    }.into_iter();
+    accesses.iter().map(|a| match a.access_type {
+        AccessType::Free => Vec::new(),
+        _ => prefetched.next().unwrap(),
+    }).collect()
+}
+
+fn sequential(degree : usize , accesses : &[MemoryAccess]) -> Vec<Vec<usize>> {
+    let mut last : Option<usize> = None;
+    let mut window = 0;
+    // The furthest page already read ahead
+    let mut ahead = 0;
+    accesses.iter().map(|access| {
+        let x = access.frame_number;
+        let sequential = last == Some(x.wrapping_sub(1));
+        last = Some(x);
+        if !sequential {
+            window = 0;
+            ahead = x;
+            return Vec::new();
+        }
+        window = (window * 2).clamp(1, degree);
+        let from = ahead.max(x) + 1;
+        ahead = ahead.max(x + window);
+        (from..=x + window).collect()
+    }).collect()
+}
+
+fn stride(degree : usize , accesses : &[MemoryAccess]) -> Vec<Vec<usize>> {
+    let mut last : Option<usize> = None;
+    let mut last_stride : Option<isize> = None;
+    let mut streaming = false;
+    accesses.iter().map(|access| {
+        let x = access.frame_number;
+        let stride = last.map(|l| x as isize - l as isize);
+        last = Some(x);
+        let confirmed = stride.is_some() && stride == last_stride && stride != Some(0);
+        last_stride = stride;
+        if !confirmed {
+            streaming = false;
+            return Vec::new();
+        }
+        let stride = stride.unwrap();
+        // The first time everything up to degree strides ahead, after that the one new page at
+        // the end of it
+        let first = if streaming { degree } else { 1 };
+        streaming = true;
+        (first..=degree)
+            .map(|k| x as isize + k as isize * stride)
+            .filter(|&page| page >= 0)
+            .map(|page| page as usize)
+            .collect()
+    }).collect()
+}
+
+fn markov(degree : usize , accesses : &[MemoryAccess]) -> Vec<Vec<usize>> {
+    // Page -> the pages that followed it and how often, in the order they first did
+    let mut successors : HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+    let mut last : Option<usize> = None;
+    accesses.iter().map(|access| {
+        let x = access.frame_number;
+        if let Some(l) = last {
+            let followers = successors.entry(l).or_default();
+            match followers.iter_mut().find(|&&mut (page, _)| page == x) {
+                Some(follower) => follower.1 += 1,
+                None => followers.push((x, 1)),
+            }
+        }
+        last = Some(x);
+        let mut followers : Vec<(usize, usize)> = successors.get(&x).cloned().unwrap_or_default();
+        // Stable, so ties go to whichever followed first
+        followers.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        followers.iter().map(|&(page, _)| page).filter(|&page| page != x).take(degree).collect()
+    }).collect()
+}
+
+// What prefetching did over a whole run
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefetchReport {
+    // The results for the demand accesses only
+    pub results : Vec<AccessResult>,
+    // Pages the prefetcher asked for after every demand access
+    pub prefetched : Vec<Vec<usize>>,
+    // Prefetches that actually read a page in
+    pub issued : usize,
+    // Prefetches of pages that were already resident
+    pub redundant : usize,
+    // Prefetched pages used before they were evicted, the PrefetchHits
+    pub useful : usize,
+    // Demand faults on pages a prefetch pushed out
+    pub pollution : usize,
+}
+
+impl PrefetchReport {
+    // Share of the pages read in ahead that got used
+    pub fn accuracy(&self) -> f64 {
+        if self.issued == 0 { 0.0 } else { self.useful as f64 / self.issued as f64 }
+    }
+
+    // Share of the faults there would have been that prefetching took care of
+    pub fn coverage(&self) -> f64 {
+        let faults = get_total_faults(&self.results) + self.useful;
+        if faults == 0 { 0.0 } else { self.useful as f64 / faults as f64 }
+    }
+}
+
+pub fn run(algorithm : &Algorithm , prefetcher : Prefetcher , degree : usize , total_frames : usize , accesses : &[MemoryAccess]) -> Result<PrefetchReport, String> {
+    let prefetched = prefetches(prefetcher, degree, accesses);
+    let mut trace : Vec<MemoryAccess> = Vec::new();
+    let mut demand : Vec<bool> = Vec::new();
+    for (access, pages) in accesses.iter().zip(prefetched.iter()) {
+        trace.push(access.clone());
+        demand.push(true);
+        for &page in pages {
+            trace.push(MemoryAccess { frame_number : page, access_type : AccessType::Read });
+            demand.push(false);
+        }
+    }
+    let all_results = (algorithm.run)(total_frames, trace.clone())?;
+
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    // Resident because of a prefetch and not used yet
+    let mut unused : HashSet<usize> = HashSet::new();
+    let mut pushed_out : HashSet<usize> = HashSet::new();
+    let (mut issued, mut redundant, mut useful, mut pollution) = (0, 0, 0, 0);
+    for ((access, result), &is_demand) in trace.iter().zip(all_results.iter()).zip(demand.iter()) {
+        let page = access.frame_number;
+        if let AccessResult::MissReplace(ref mr) = *result {
+            unused.remove(&mr.replaced);
+            if !is_demand {
+                pushed_out.insert(mr.replaced);
+            }
+        }
+        // Faulting back in after a free is on the trace, not the prefetcher
+        if *result == AccessResult::Freed {
+            pushed_out.remove(&page);
+        }
+        if result.is_fault() && pushed_out.remove(&page) && is_demand {
+            pollution += 1;
+        }
+        if !is_demand {
+            if result.is_hit() {
+                redundant += 1;
+            } else {
+                issued += 1;
+                unused.insert(page);
+            }
+        } else if result.is_hit() && unused.remove(&page) {
+            useful += 1;
+            results.push(AccessResult::PrefetchHit);
+        } else {
+            unused.remove(&page);
+            results.push(result.clone());
+        }
+    }
+
+    Ok(PrefetchReport { results, prefetched, issued, redundant, useful, pollution })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compare::algorithm;
+
+    fn trace(s : &str) -> Vec<MemoryAccess> {
+        MemoryAccess::create(String::from(s))
+    }
+
+    #[test]
+    fn sequential_window_doubles_and_closes_on_a_jump() {
+        let prefetched = prefetches(Prefetcher::Sequential, 4, &trace("R:1 R:2 R:3 R:4 R:9 R:10"));
+        let expected : Vec<Vec<usize>> = vec![vec![], vec![3], vec![4, 5], vec![6, 7, 8], vec![], vec![11]];
+        assert_eq!(prefetched , expected);
+    }
+
+    #[test]
+    fn stride_needs_two_matching_strides() {
+        let prefetched = prefetches(Prefetcher::Stride, 2, &trace("R:0 R:3 R:6 R:9 R:4"));
+        let expected : Vec<Vec<usize>> = vec![vec![], vec![], vec![9, 12], vec![15], vec![]];
+        assert_eq!(prefetched , expected);
+    }
+
+    #[test]
+    fn markov_prefetches_the_most_common_successors() {
+        let prefetched = prefetches(Prefetcher::Markov, 1, &trace("R:1 R:2 R:1 R:3 R:1 R:3 R:1"));
+        // 2 followed 1 first, then 3 catches up and passes it
+        assert_eq!(prefetched[2] , vec![2]);
+        assert_eq!(prefetched[4] , vec![2]);
+        assert_eq!(prefetched[6] , vec![3]);
+        assert_eq!(prefetches(Prefetcher::Markov, 2, &trace("R:1 R:2 R:1 R:3 R:1"))[4] , vec![2, 3]);
+    }
+
+    #[test]
+    fn prefetched_pages_are_used_as_prefetch_hits() {
+        let accesses = trace("R:1 R:2 R:3 R:4 R:5 R:6");
+        let second = algorithm("second", 0).unwrap();
+        let report = run(&second, Prefetcher::Sequential, 2, 4, &accesses).unwrap();
+        assert_eq!(report.results.len() , accesses.len());
+        // 1 and 2 fault, 3 is read ahead after 2, then 4 and 5 after 3 and 6 after 4, and 7 and 8
+        // after 5 and 6 go unused
+        assert_eq!(get_total_faults(&report.results) , 2);
+        assert_eq!(report.useful , 4);
+        assert_eq!(report.results.iter().filter(|r| **r == AccessResult::PrefetchHit).count() , 4);
+        assert_eq!(report.issued , 6);
+        assert_eq!(report.accuracy() , 4.0 / 6.0);
+        assert_eq!(report.coverage() , 4.0 / 6.0);
+        assert_eq!(report.pollution , 0);
+    }
+
+    #[test]
+    fn prefetch_that_pushes_out_a_page_in_use_is_pollution() {
+        // 3 is read ahead after 2 and takes 1's frame
+        let accesses = trace("R:1 R:2 R:1");
+        let second = algorithm("second", 0).unwrap();
+        let report = run(&second, Prefetcher::Sequential, 1, 2, &accesses).unwrap();
+        assert_eq!(report.issued , 1);
+        assert_eq!(report.useful , 0);
+        assert_eq!(report.pollution , 1);
+        assert_eq!(get_total_faults(&report.results) , 3);
+    }
+}
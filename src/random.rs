@@ -0,0 +1,56 @@
+// A tiny seeded random number generator, so runs that pick randomly can be repeated exactly
+// (and unit tested), splitmix64 because it's a handful of lines and good enough for picking victims
+#[derive(Debug, Clone)]
+pub struct Random {
+    state : u64,
+}
+
+impl Random {
+    // Constructor, the same seed always gives the same sequence
+    pub fn new(seed : u64) -> Random {
+        Random { state : seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Something in 0..bound, bound has to be at least 1
+    pub fn below(&mut self , bound : usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    // Something in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = Random::new(42);
+        let mut b = Random::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64() , b.next_u64());
+        }
+        assert_ne!(Random::new(1).next_u64() , Random::new(2).next_u64());
+    }
+
+    #[test]
+    fn stays_in_bounds() {
+        let mut random = Random::new(7);
+        for _ in 0..1000 {
+            assert!(random.below(3) < 3);
+            let f = random.next_f64();
+            assert!((0.0..1.0).contains(&f));
+        }
+    }
+}
@@ -0,0 +1,245 @@
+// Self-contained HTML reports with inline SVG charts, nothing is fetched from the outside world
+use compare::Summary;
+use {exit_on_error, parse_file, parse_number};
+use {AccessResult, MemoryAccess};
+
+// Report parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct REPORT_Params {
+    pub total_frames : usize,
+    pub tau : usize,
+    pub filename : String,
+    pub access_string : String,
+    pub output : String,
+}
+
+impl REPORT_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> REPORT_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let frames = exit_on_error(parse_number("frame count", args.get(1)));
+        let tau = exit_on_error(parse_number("tau", args.get(2)));
+        let file = exit_on_error(parse_file(args.get(3)));
+        let output = exit_on_error(args.get(4).cloned().ok_or_else(|| String::from("No output file provided")));
+        REPORT_Params {
+            total_frames : frames,
+            tau,
+            filename : args[3].clone(),
+            access_string : file,
+            output,
+        }
+    }
+}
+
+// Everything the report needs to know about one algorithm's run
+pub struct AlgorithmRun {
+    pub summary : Summary,
+    pub results : Vec<AccessResult>,
+    // Miss ratio for 1, 2, 3... frames, None where the trace pins every frame
+    pub miss_ratio_curve : Vec<Option<f64>>,
+}
+
+// A stretch of time [start, end) during which a page sat in physical memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Residency {
+    pub page : usize,
+    pub start : usize,
+    pub end : usize,
+}
+
+// Replays the results to find out when each page came in and when it got evicted, the
+// MissReplacement records are enough to do that without knowing anything about the algorithm
+pub fn residency_intervals(accesses : &[MemoryAccess] , results : &[AccessResult]) -> Vec<Residency> {
+    let mut open : Vec<(usize, usize)> = Vec::new();
+    let mut closed : Vec<Residency> = Vec::new();
+    for (time, (access, result)) in accesses.iter().zip(results.iter()).enumerate() {
+        match *result {
+            AccessResult::Hit | AccessResult::PrefetchHit => (),
+            AccessResult::MissSimple => open.push((access.frame_number, time)),
+            AccessResult::Freed => {
+                if let Some(i) = open.iter().position(|&(page, _)| page == access.frame_number) {
+                    let (page, start) = open.remove(i);
+                    closed.push(Residency { page, start, end : time });
+                }
+            }
+            AccessResult::MissReplace(ref mr) => {
+                if let Some(i) = open.iter().position(|&(page, _)| page == mr.replaced) {
+                    let (page, start) = open.remove(i);
+                    closed.push(Residency { page, start, end : time });
+                }
+                open.push((mr.new_page, time));
+            }
+        }
+    }
+    let end = results.len();
+    closed.extend(open.into_iter().map(|(page, start)| Residency { page, start, end }));
+    closed.sort_by_key(|r| (r.page, r.start));
+    closed
+}
+
+const COLORS : [&str; 10] = ["#1f77b4", "#d62728", "#2ca02c", "#9467bd", "#ff7f0e",
+                             "#8c564b", "#e377c2", "#7f7f7f", "#bcbd22", "#17becf"];
+
+pub fn escape_html(text : &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Line chart of every algorithm's miss ratio against the number of frames
+fn miss_ratio_svg(runs : &[AlgorithmRun] , total_frames : usize) -> String {
+    let (width, height, margin) = (640.0, 320.0, 40.0);
+    let max_frames = runs.iter().map(|r| r.miss_ratio_curve.len()).max().unwrap_or(0).max(2);
+    let x = |frames : usize| margin + (frames - 1) as f64 * (width - 2.0 * margin) / (max_frames - 1) as f64;
+    let y = |ratio : f64| height - margin - ratio * (height - 2.0 * margin);
+
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n", width, height);
+    svg.push_str(&format!("<line x1=\"{0}\" y1=\"{1}\" x2=\"{2}\" y2=\"{1}\" stroke=\"black\"/>\n",
+                          margin, height - margin, width - margin));
+    svg.push_str(&format!("<line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"black\"/>\n",
+                          margin, margin, height - margin));
+    for tick in 0..5 {
+        let ratio = tick as f64 / 4.0;
+        svg.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"end\">{:.2}</text>\n",
+                              margin - 4.0, y(ratio) + 3.0, ratio));
+    }
+    for frames in 1..=max_frames {
+        svg.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"middle\">{}</text>\n",
+                              x(frames), height - margin + 14.0, frames));
+    }
+    if total_frames >= 1 && total_frames <= max_frames {
+        svg.push_str(&format!("<line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"gray\" stroke-dasharray=\"4\"/>\n",
+                              x(total_frames), margin, height - margin));
+    }
+    for (i, run) in runs.iter().enumerate() {
+        let color = COLORS[i % COLORS.len()];
+        let points : Vec<String> = run.miss_ratio_curve.iter().enumerate()
+            .filter_map(|(f, ratio)| ratio.map(|ratio| format!("{:.1},{:.1}", x(f + 1), y(ratio))))
+            .collect();
+        svg.push_str(&format!("<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" points=\"{}\"/>\n",
+                              color, points.join(" ")));
+        svg.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"12\" fill=\"{}\">{}</text>\n",
+                              width - margin - 80.0, margin + 14.0 * i as f64, color, escape_html(&run.summary.name)));
+    }
+    svg.push_str("<text x=\"320\" y=\"316\" font-size=\"11\" text-anchor=\"middle\">frames</text>\n");
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// One row per page, shaded while the page is resident, with a red tick on every fault
+fn residency_svg(accesses : &[MemoryAccess] , results : &[AccessResult]) -> String {
+    let mut pages : Vec<usize> = accesses.iter().map(|a| a.frame_number).collect();
+    pages.sort();
+    pages.dedup();
+    let row = |page : usize| pages.binary_search(&page).unwrap_or(0) as f64;
+    let (label_width, row_height) = (50.0, 14.0);
+    let cell = (800.0 / accesses.len().max(1) as f64).clamp(2.0, 24.0);
+    let width = label_width + cell * accesses.len() as f64 + 10.0;
+    let height = row_height * pages.len() as f64 + 20.0;
+
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\">\n", width, height);
+    for (i, page) in pages.iter().enumerate() {
+        svg.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"end\">{}</text>\n",
+                              label_width - 6.0, row_height * i as f64 + 10.0, page));
+    }
+    for r in residency_intervals(accesses, results) {
+        svg.push_str(&format!("<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#9ecae1\"/>\n",
+                              label_width + cell * r.start as f64, row_height * row(r.page) + 1.0,
+                              cell * (r.end - r.start) as f64, row_height - 2.0));
+    }
+    for (time, (access, result)) in accesses.iter().zip(results.iter()).enumerate() {
+        if result.is_fault() {
+            svg.push_str(&format!("<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#d62728\"><title>t={} fault on {}</title></rect>\n",
+                                  label_width + cell * time as f64, row_height * row(access.frame_number) + 1.0,
+                                  (cell / 3.0).max(1.0), row_height - 2.0, time, access.frame_number));
+        }
+    }
+    svg.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"10\">time &#8594;</text>\n",
+                          label_width, height - 4.0));
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn summary_html(runs : &[AlgorithmRun]) -> String {
+    let mut table = String::from("<table>\n<tr><th>algorithm</th><th>accesses</th><th>hits</th><th>simple misses</th>\
+                                  <th>replacements</th><th>faults</th><th>miss ratio</th></tr>\n");
+    for run in runs {
+        let s = &run.summary;
+        table.push_str(&format!("<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.3}</td></tr>\n",
+                                escape_html(&s.name), s.accesses, s.hits, s.simple_misses, s.replacements,
+                                s.faults(), s.miss_ratio()));
+    }
+    table.push_str("</table>\n");
+    table
+}
+
+// The whole page, styles and charts inline so the file can be mailed around on its own
+pub fn render_html(title : &str , total_frames : usize , accesses : &[MemoryAccess] , runs : &[AlgorithmRun]) -> String {
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape_html(title)));
+    html.push_str("<style>\nbody { font-family: sans-serif; margin: 2em; }\n\
+                   table { border-collapse: collapse; }\n\
+                   td, th { border: 1px solid #ccc; padding: 4px 8px; text-align: right; }\n\
+                   .heatmap { overflow-x: auto; }\n</style>\n</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(title)));
+    html.push_str(&format!("<p>{} accesses, {} frames</p>\n", accesses.len(), total_frames));
+    html.push_str("<h2>Summary</h2>\n");
+    html.push_str(&summary_html(runs));
+    html.push_str("<h2>Miss ratio curve</h2>\n");
+    html.push_str(&miss_ratio_svg(runs, total_frames));
+    html.push_str("<h2>Page residency</h2>\n<p>Shaded while resident, red marks are faults.</p>\n");
+    for run in runs {
+        html.push_str(&format!("<h3>{}</h3>\n<div class=\"heatmap\">\n", escape_html(&run.summary.name)));
+        html.push_str(&residency_svg(accesses, &run.results));
+        html.push_str("</div>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compare::{algorithms, miss_ratio_curve, distinct_pages};
+    use MissReplacement;
+
+    #[test]
+    fn residency_closes_interval_of_replaced_page() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:2"));
+        let results = vec![
+            AccessResult::MissSimple,
+            AccessResult::MissSimple,
+            AccessResult::MissReplace(MissReplacement::new(1, 0, 3)),
+            AccessResult::Hit,
+        ];
+        let intervals = residency_intervals(&accesses, &results);
+        assert_eq!(intervals , vec![
+            Residency { page : 1, start : 0, end : 2 },
+            Residency { page : 2, start : 1, end : 4 },
+            Residency { page : 3, start : 2, end : 4 },
+        ]);
+    }
+
+    #[test]
+    fn html_is_self_contained_and_lists_every_algorithm() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:2 R:1"));
+        let runs : Vec<AlgorithmRun> = algorithms(5).iter().map(|a| {
+            let results = (a.run)(3, accesses.clone()).unwrap();
+            AlgorithmRun {
+                summary : Summary::new(a.name, &results),
+                miss_ratio_curve : miss_ratio_curve(a, &accesses, distinct_pages(&accesses)).unwrap(),
+                results,
+            }
+        }).collect();
+        let html = render_html("<trace>", 3, &accesses, &runs);
+        assert!(html.contains("&lt;trace&gt;"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("src=\"http"));
+        assert!(!html.contains("href=\"http"));
+        for name in &["optimal", "second", "wsclock"] {
+            assert!(html.contains(&format!("<td>{}</td>", name)));
+        }
+    }
+}
@@ -0,0 +1,182 @@
+// S3-FIFO: three FIFO queues. New pages go into the small queue S (a tenth of memory), and most
+// of them are only used once and leave from there quickly. A page that got hit while in S moves
+// to the main queue M instead of being evicted. Pages evicted from S are remembered in the ghost
+// queue G (page numbers only, no frames), and one that faults back in while remembered goes
+// straight to M. M evicts like a clock, a page with hits left goes back in at the head with one
+// less, up to 3 are counted.
+use std::collections::VecDeque;
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+const MAX_FREQUENCY : u8 = 3;
+
+pub fn process_page_requests(total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<usize>) -> Result<Vec<AccessResult>, String> {
+    let small_size = (total_physical_pages / 10).max(1);
+    let ghost_size = total_physical_pages.saturating_sub(small_size).max(1);
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    // Oldest at the front for all three
+    let mut queues = Queues::default();
+    let mut pins = Pins::new();
+
+    for access in accesses.iter() {
+        pins.update(access);
+        let x = access.frame_number;
+        // A freed page leaves whichever queue it's in, G forgets it too
+        if access.access_type == AccessType::Free {
+            queues.small.retain(|p| p.number != x);
+            queues.main.retain(|p| p.number != x);
+            queues.ghost.retain(|&p| p != x);
+            v_memory.retain(|&p| p != x);
+            results.push(AccessResult::Freed);
+            continue;
+        }
+        if let Some(page) = queues.small.iter_mut().chain(queues.main.iter_mut()).find(|p| p.number == x) {
+            page.frequency = (page.frequency + 1).min(MAX_FREQUENCY);
+            results.push(AccessResult::Hit);
+            continue;
+        }
+
+        // Has to be checked before evicting, which could push it out of G
+        let remembered = match queues.ghost.iter().position(|&p| p == x) {
+            Some(i) => {
+                queues.ghost.remove(i);
+                true
+            }
+            None => false,
+        };
+
+        if v_memory.len() < total_physical_pages {
+            v_memory.push(x);
+            results.push(AccessResult::MissSimple);
+        } else {
+            pins.check_evictable(total_physical_pages, x)?;
+            let victim = if queues.small.len() >= small_size {
+                queues.evict_small(ghost_size, &pins)
+            } else {
+                queues.evict_main(ghost_size, &pins)
+            };
+            let index = v_memory.iter().position(|&p| p == victim).unwrap();
+            results.push(AccessResult::MissReplace(MissReplacement::new(victim, index, x)));
+            v_memory[index] = x;
+        }
+
+        let page = Page { number : x, frequency : 0 };
+        if remembered {
+            queues.main.push_back(page);
+        } else {
+            queues.small.push_back(page);
+        }
+    }
+
+    Ok(results)
+}
+
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub number : usize,
+    // Hits since it got into its queue, or since the last time M gave it another lap
+    pub frequency : u8,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Queues {
+    pub small : VecDeque<Page>,
+    pub main : VecDeque<Page>,
+    pub ghost : VecDeque<usize>,
+}
+
+impl Queues {
+    // Pages that were hit in S move to M, the first one that wasn't is evicted and remembered in G.
+    // If everything in S moved, M has to give up a page instead. Pinned pages move to M like hit ones.
+    fn evict_small(&mut self , ghost_size : usize , pins : &Pins) -> usize {
+        while let Some(mut page) = self.small.pop_front() {
+            if pins.contains(page.number) {
+                self.main.push_back(page);
+            } else if page.frequency > 0 {
+                page.frequency = 0;
+                self.main.push_back(page);
+            } else {
+                self.ghost.push_back(page.number);
+                if self.ghost.len() > ghost_size {
+                    self.ghost.pop_front();
+                }
+                return page.number;
+            }
+        }
+        self.evict_main(ghost_size, pins)
+    }
+
+    // Goes around M until a page without hits left is at the tail, pinned pages just go around. If
+    // everything in M is pinned, S has to give up a page instead.
+    fn evict_main(&mut self , ghost_size : usize , pins : &Pins) -> usize {
+        if self.main.iter().all(|p| pins.contains(p.number)) {
+            return self.evict_small(ghost_size, pins);
+        }
+        loop {
+            let mut page = self.main.pop_front().unwrap();
+            if pins.contains(page.number) {
+                self.main.push_back(page);
+            } else if page.frequency > 0 {
+                page.frequency -= 1;
+                self.main.push_back(page);
+            } else {
+                return page.number;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 W:1 R:1 W:1"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        for result in results.iter().skip(1) {
+            assert_eq!(*result , AccessResult::Hit);
+        }
+    }
+
+    #[test]
+    fn page_hit_in_small_moves_to_main_and_survives_a_scan() {
+        // Everything starts in S, 1 is hit there so it moves to M when 4 needs a frame and 2 goes.
+        // The scan after that only churns through S.
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1 R:4 R:5 R:6 R:7 R:1"));
+        let total_pages = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(2, 1, 4)));
+        assert_eq!(results[8] , AccessResult::Hit);
+    }
+
+    #[test]
+    fn page_back_from_the_ghost_queue_goes_to_main() {
+        // 1 leaves S without a hit and is remembered, when it comes back it goes into M, and 3
+        // (still in S) goes before it
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1 R:4 R:5"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[2] , AccessResult::MissReplace(MissReplacement::new(1, 0, 3)));
+        assert_eq!(results[3] , AccessResult::MissReplace(MissReplacement::new(2, 1, 1)));
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(3, 0, 4)));
+        assert_eq!(results[5] , AccessResult::MissReplace(MissReplacement::new(4, 0, 5)));
+    }
+}
@@ -0,0 +1,132 @@
+// SIEVE: a FIFO queue with a visited bit per page, like Second Chance, except nothing ever moves.
+// The hand walks from the oldest page towards the newest clearing visited bits and evicts the
+// first page that wasn't visited, then stays where it is for the next eviction. New pages always
+// go in at the newest end, so pages the hand already passed over keep their spot and one-off pages
+// from a scan are evicted quickly.
+use std::collections::VecDeque;
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+pub fn process_page_requests(total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<usize>) -> Result<Vec<AccessResult>, String> {
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    // Oldest at the front, newest at the back
+    let mut queue : VecDeque<Page> = VecDeque::with_capacity(total_physical_pages);
+    // Index into the queue, it starts at the oldest page
+    let mut hand = 0;
+    let mut pins = Pins::new();
+
+    for access in accesses.iter() {
+        pins.update(access);
+        let x = access.frame_number;
+        if access.access_type == AccessType::Free {
+            // Nothing else moves, the hand stays on the page it was on
+            if let Some(index) = queue.iter().position(|p| p.number == x) {
+                queue.remove(index);
+                if index < hand {
+                    hand -= 1;
+                }
+            }
+            v_memory.retain(|&p| p != x);
+            results.push(AccessResult::Freed);
+            continue;
+        }
+        if let Some(page) = queue.iter_mut().find(|p| p.number == x) {
+            page.visited = true;
+            results.push(AccessResult::Hit);
+            continue;
+        }
+
+        if v_memory.len() < total_physical_pages {
+            v_memory.push(x);
+            results.push(AccessResult::MissSimple);
+        } else {
+            pins.check_evictable(total_physical_pages, x)?;
+            loop {
+                if hand >= queue.len() {
+                    // Went past the newest page, back around to the oldest
+                    hand = 0;
+                }
+                if pins.contains(queue[hand].number) {
+                    hand += 1;
+                } else if queue[hand].visited {
+                    queue[hand].visited = false;
+                    hand += 1;
+                } else {
+                    break;
+                }
+            }
+            // The hand ends up on the page that came after the victim
+            let victim = queue.remove(hand).unwrap();
+            let index = v_memory.iter().position(|&p| p == victim.number).unwrap();
+            results.push(AccessResult::MissReplace(MissReplacement::new(victim.number, index, x)));
+            v_memory[index] = x;
+        }
+        queue.push_back(Page { number : x, visited : false });
+    }
+
+    Ok(results)
+}
+
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub number : usize,
+    pub visited : bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {get_total_faults, second};
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 W:1 R:1 W:1"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        for result in results.iter().skip(1) {
+            assert_eq!(*result , AccessResult::Hit);
+        }
+    }
+
+    #[test]
+    fn hand_stays_put_after_an_eviction() {
+        // 1 is visited so 2 goes for 4, the hand stays on 3 which goes for 5 even though 1 was
+        // never visited again, since the hand already passed it
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1 R:4 R:5"));
+        let total_pages = 3;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(2, 1, 4)));
+        assert_eq!(results[5] , AccessResult::MissReplace(MissReplacement::new(3, 2, 5)));
+    }
+
+    #[test]
+    fn hot_page_outlasts_a_scan_unlike_second_chance() {
+        // Second Chance lets new pages in with their referenced bit set, so once in a while 1 is
+        // the only unreferenced page left and goes
+        let mut trace = String::from("R:1");
+        for page in 10..20 {
+            trace.push_str(&format!(" R:{} R:1", page));
+        }
+        let accesses = MemoryAccess::create(trace);
+        let sieve = process_page_requests(3, accesses.clone(), Vec::with_capacity(3)).unwrap();
+        let second = second::process_page_requests(3, accesses, VecDeque::with_capacity(3)).unwrap();
+        for time in (2..sieve.len()).step_by(2) {
+            assert_eq!(sieve[time] , AccessResult::Hit);
+        }
+        assert!(get_total_faults(&sieve) < get_total_faults(&second));
+    }
+}
@@ -0,0 +1,378 @@
+// A stack of memory tiers instead of a single level of frames, like fast DRAM, then slower CXL or
+// NVM memory, then swap. Every tier has its own capacity, latency and replacement policy, and a
+// page lives in exactly one of them at a time.
+//   A fault comes in from swap into the top tier.
+//   A tier that's full demotes its victim to the tier below, which may demote one of its own, and
+//   the bottom tier's victim goes out to swap.
+//   A hit in a lower tier is served from there, and once a page has been hit promote_after times
+//   in the same tier it's promoted one tier up, swapping places with that tier's victim.
+//   A freed page just leaves whichever tier it's in.
+//   A pinned page stays in the tier it's in. It's never a victim, and a page coming into a tier
+//   that only has pinned pages left goes on to the tier below instead.
+use tlb::DEFAULT_LATENCIES;
+use {exit_on_error, parse_file, parse_nonzero, parse_number};
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+// How a full tier picks its victim
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TierPolicy {
+    Lru,
+    Fifo,
+    Lfu,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierSpec {
+    pub name : String,
+    pub frames : usize,
+    // Nanoseconds to serve an access from this tier
+    pub latency : usize,
+    pub policy : TierPolicy,
+}
+
+// Tiers top first separated by commas, each NAME:FRAMES:LATENCY_NS:POLICY with lru, fifo or lfu,
+// like dram:4:100:lru,cxl:16:300:fifo
+pub fn parse_tiers(tiers_string : Option<&String>) -> Result<Vec<TierSpec>, String> {
+    let tiers_string = tiers_string.ok_or_else(|| String::from("No tiers provided"))?;
+    tiers_string.split(',').map(|tier| {
+        let parts : Vec<String> = tier.split(':').map(String::from).collect();
+        if parts.len() != 4 {
+            return Err(format!("Tier must be NAME:FRAMES:LATENCY_NS:POLICY, not {}", tier));
+        }
+        let policy = match parts[3].as_str() {
+            "lru" => TierPolicy::Lru,
+            "fifo" => TierPolicy::Fifo,
+            "lfu" => TierPolicy::Lfu,
+            p => return Err(format!("Tier policy must be lru, fifo or lfu, not {}", p)),
+        };
+        Ok(TierSpec {
+            name : parts[0].clone(),
+            frames : parse_nonzero("tier frame count", parts.get(1))?,
+            latency : parse_number("tier latency", parts.get(2))?,
+            policy,
+        })
+    }).collect()
+}
+
+// Tiered memory parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct TIERED_Params {
+    pub tiers : Vec<TierSpec>,
+    pub promote_after : usize,
+    // Nanoseconds for a fault from swap
+    pub swap_latency : usize,
+    pub access_string : String,
+}
+
+impl TIERED_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> TIERED_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let tiers = exit_on_error(parse_tiers(args.get(1)));
+        let promote_after = exit_on_error(parse_nonzero("promotion threshold", args.get(2)));
+        let file = exit_on_error(parse_file(args.get(3)));
+        TIERED_Params {
+            tiers,
+            promote_after,
+            swap_latency : DEFAULT_LATENCIES.fault,
+            access_string : file,
+        }
+    }
+}
+
+// A page moving between tiers, to None when it's pushed out to swap
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub page : usize,
+    pub from : usize,
+    pub to : Option<usize>,
+}
+
+// What happened on one access
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierAccess {
+    // The tier that served it, None for a fault from swap
+    pub served : Option<usize>,
+    pub promoted : bool,
+    pub demotions : Vec<Migration>,
+}
+
+#[derive(Debug, Clone)]
+struct Resident {
+    number : usize,
+    loaded : usize,
+    last_used : usize,
+    // Hits since it came into this tier
+    count : usize,
+}
+
+#[derive(Debug, Clone)]
+struct Tier {
+    frames : usize,
+    policy : TierPolicy,
+    pages : Vec<Resident>,
+}
+
+impl Tier {
+    // None when every page in the tier is pinned
+    fn victim(&self , pins : &Pins) -> Option<usize> {
+        let pages = self.pages.iter().enumerate().filter(|&(_, p)| !pins.contains(p.number));
+        let victim = match self.policy {
+            TierPolicy::Lru => pages.min_by_key(|&(_, p)| p.last_used),
+            TierPolicy::Fifo => pages.min_by_key(|&(_, p)| p.loaded),
+            TierPolicy::Lfu => pages.min_by_key(|&(_, p)| (p.count, p.last_used)),
+        };
+        victim.map(|(i, _)| i)
+    }
+
+    fn has_room(&self , pins : &Pins) -> bool {
+        self.pages.len() < self.frames || self.victim(pins).is_some()
+    }
+}
+
+// Puts the page in the tier, demoting victims down the stack as far as they have to go. Gives back
+// the tier and slot the page ended up in, or None if it went out to swap.
+fn insert(tiers : &mut [Tier] , level : usize , page : Resident , time : usize , pins : &Pins , demotions : &mut Vec<Migration>) -> Option<(usize, usize)> {
+    let page = Resident { loaded : time, count : 0, ..page };
+    let tier = &mut tiers[level];
+    if tier.pages.len() < tier.frames {
+        tier.pages.push(page);
+        return Some((level, tier.pages.len() - 1));
+    }
+    let slot = match tier.victim(pins) {
+        Some(slot) => slot,
+        // Nothing here can move, so the page itself carries on down
+        None if level + 1 < tiers.len() => return insert(tiers, level + 1, page, time, pins, demotions),
+        None => return None,
+    };
+    let victim = std::mem::replace(&mut tier.pages[slot], page);
+    if level + 1 < tiers.len() {
+        let demotion = demotions.len();
+        demotions.push(Migration { page : victim.number, from : level, to : Some(level + 1) });
+        // Everything below is pinned solid, so the victim goes out to swap after all
+        if insert(tiers, level + 1, victim, time, pins, demotions).is_none() {
+            demotions[demotion].to = None;
+        }
+    } else {
+        demotions.push(Migration { page : victim.number, from : level, to : None });
+    }
+    Some((level, slot))
+}
+
+pub fn process_page_requests(specs : &[TierSpec] , promote_after : usize , accesses : Vec<MemoryAccess>) -> Result<Vec<AccessResult>, String> {
+    Ok(process_page_requests_with_tiers(specs, promote_after, accesses)?.0)
+}
+
+// Same as above, but also hands back what every access did to the tiers. A page is a hit as long as
+// it's in one of them, and a MissReplace names the page pushed out to swap and the slot the new page
+// took, in the top tier unless that one was all pinned.
+pub fn process_page_requests_with_tiers(specs : &[TierSpec] , promote_after : usize , accesses : Vec<MemoryAccess>) -> Result<(Vec<AccessResult>, Vec<TierAccess>), String> {
+    let mut tiers : Vec<Tier> = specs.iter().map(|s| Tier { frames : s.frames, policy : s.policy, pages : Vec::with_capacity(s.frames) }).collect();
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut tier_accesses : Vec<TierAccess> = Vec::with_capacity(accesses.len());
+    let mut pins = Pins::new();
+    let total_frames : usize = specs.iter().map(|s| s.frames).sum();
+
+    for (time, access) in accesses.iter().enumerate() {
+        pins.update(access);
+        let x = access.frame_number;
+        let mut demotions : Vec<Migration> = Vec::new();
+        let found = tiers.iter().enumerate()
+            .find_map(|(level, tier)| tier.pages.iter().position(|p| p.number == x).map(|i| (level, i)));
+
+        if access.access_type == AccessType::Free {
+            if let Some((level, i)) = found {
+                tiers[level].pages.remove(i);
+            }
+            results.push(AccessResult::Freed);
+            tier_accesses.push(TierAccess { served : None, promoted : false, demotions });
+            continue;
+        }
+        match found {
+            Some((level, i)) => {
+                let page = &mut tiers[level].pages[i];
+                page.last_used = time;
+                page.count += 1;
+                let promoted = level > 0 && page.count >= promote_after && tiers[level - 1].has_room(&pins);
+                if promoted {
+                    // The tier above demotes its victim into the slot this leaves
+                    let page = tiers[level].pages.remove(i);
+                    insert(&mut tiers, level - 1, page, time, &pins, &mut demotions);
+                }
+                results.push(AccessResult::Hit);
+                tier_accesses.push(TierAccess { served : Some(level), promoted, demotions });
+            }
+            None => {
+                let page = Resident { number : x, loaded : time, last_used : time, count : 0 };
+                // Only out of room when every tier is all pinned
+                let (_, slot) = insert(&mut tiers, 0, page, time, &pins, &mut demotions)
+                    .ok_or_else(|| format!("All {} frames are pinned, nothing can be evicted for page {}", total_frames, x))?;
+                match demotions.iter().find(|m| m.to.is_none()) {
+                    Some(m) => results.push(AccessResult::MissReplace(MissReplacement::new(m.page, slot, x))),
+                    None => results.push(AccessResult::MissSimple),
+                }
+                tier_accesses.push(TierAccess { served : None, promoted : false, demotions });
+            }
+        }
+    }
+
+    Ok((results, tier_accesses))
+}
+
+// How one tier did
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TierSummary {
+    pub hits : usize,
+    pub promotions_in : usize,
+    pub promotions_out : usize,
+    pub demotions_in : usize,
+    pub demotions_out : usize,
+}
+
+// One summary per tier, top first
+pub fn summarize(tier_count : usize , tier_accesses : &[TierAccess]) -> Vec<TierSummary> {
+    let mut summaries = vec![TierSummary::default(); tier_count];
+    for access in tier_accesses {
+        if let Some(level) = access.served {
+            summaries[level].hits += 1;
+            if access.promoted {
+                summaries[level].promotions_out += 1;
+                summaries[level - 1].promotions_in += 1;
+            }
+        }
+        for m in &access.demotions {
+            summaries[m.from].demotions_out += 1;
+            if let Some(to) = m.to {
+                summaries[to].demotions_in += 1;
+            }
+        }
+    }
+    summaries
+}
+
+// Average nanoseconds per access, the latency of whichever tier served it or of swap. Migrations
+// are assumed to happen in the background. Frees aren't accesses and don't count.
+pub fn average_latency(specs : &[TierSpec] , swap_latency : usize , results : &[AccessResult] , tier_accesses : &[TierAccess]) -> f64 {
+    let count = results.iter().filter(|r| **r != AccessResult::Freed).count();
+    if count == 0 {
+        return 0.0;
+    }
+    let total : usize = results.iter().zip(tier_accesses.iter())
+        .filter(|&(result, _)| *result != AccessResult::Freed)
+        .map(|(_, a)| a.served.map_or(swap_latency, |level| specs[level].latency))
+        .sum();
+    total as f64 / count as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    fn tiers(spec : &str) -> Vec<TierSpec> {
+        parse_tiers(Some(&String::from(spec))).unwrap()
+    }
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let results = process_page_requests(&tiers("dram:2:100:lru,nvm:2:300:lru"), 1, accesses).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 R:1"));
+        let results = process_page_requests(&tiers("dram:1:100:lru"), 1, accesses).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::Hit);
+        assert_eq!(results[2] , AccessResult::Hit);
+    }
+
+    #[test]
+    fn pages_are_promoted_after_enough_hits() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1 R:1"));
+        let (results, tier_accesses) = process_page_requests_with_tiers(&tiers("dram:2:100:lru,nvm:2:300:lru"), 2, accesses).unwrap();
+        // 3 pushes 1 down instead of out
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(tier_accesses[2].demotions , vec![Migration { page : 1, from : 0, to : Some(1) }]);
+        // The first hit on 1 down there isn't enough to move it up
+        assert_eq!(tier_accesses[3] , TierAccess { served : Some(1), promoted : false, demotions : Vec::new() });
+        // The second one is, and 2 takes its place
+        assert_eq!(tier_accesses[4] , TierAccess { served : Some(1), promoted : true, demotions : vec![Migration { page : 2, from : 0, to : Some(1) }] });
+    }
+
+    #[test]
+    fn bottom_tier_victims_go_to_swap() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3"));
+        let (results, tier_accesses) = process_page_requests_with_tiers(&tiers("dram:1:100:lru,nvm:1:300:lru"), 1, accesses).unwrap();
+        assert_eq!(results[2] , AccessResult::MissReplace(MissReplacement::new(1, 0, 3)));
+        assert_eq!(tier_accesses[2].demotions , vec![
+            Migration { page : 2, from : 0, to : Some(1) },
+            Migration { page : 1, from : 1, to : None },
+        ]);
+    }
+
+    #[test]
+    fn every_tier_evicts_with_its_own_policy() {
+        // 1 is used again before 3 comes in, LRU keeps it and FIFO doesn't
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1 R:3"));
+        let lru = process_page_requests(&tiers("dram:2:100:lru"), 1, accesses.clone()).unwrap();
+        let fifo = process_page_requests(&tiers("dram:2:100:fifo"), 1, accesses.clone()).unwrap();
+        let lfu = process_page_requests(&tiers("dram:2:100:lfu"), 1, accesses).unwrap();
+        assert_eq!(lru[3] , AccessResult::MissReplace(MissReplacement::new(2, 1, 3)));
+        assert_eq!(fifo[3] , AccessResult::MissReplace(MissReplacement::new(1, 0, 3)));
+        assert_eq!(lfu[3] , AccessResult::MissReplace(MissReplacement::new(2, 1, 3)));
+    }
+
+    #[test]
+    fn hits_migrations_and_latency_add_up_per_tier() {
+        let specs = tiers("dram:2:100:lru,nvm:2:300:lru");
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1 R:1"));
+        let (results, tier_accesses) = process_page_requests_with_tiers(&specs, 2, accesses).unwrap();
+        let summaries = summarize(specs.len(), &tier_accesses);
+        assert_eq!(summaries[0] , TierSummary { hits : 0, promotions_in : 1, promotions_out : 0, demotions_in : 0, demotions_out : 2 });
+        assert_eq!(summaries[1] , TierSummary { hits : 2, promotions_in : 0, promotions_out : 1, demotions_in : 2, demotions_out : 0 });
+        assert_eq!(average_latency(&specs, 1000, &results, &tier_accesses) , (3.0 * 1000.0 + 2.0 * 300.0) / 5.0);
+    }
+
+    #[test]
+    fn pinned_pages_are_never_demoted() {
+        let accesses = MemoryAccess::create(String::from("L:1 R:2 R:3 R:4"));
+        let (results, tier_accesses) = process_page_requests_with_tiers(&tiers("dram:1:100:lru,nvm:1:300:lru"), 1, accesses).unwrap();
+        // 1 holds on to dram, so 2 goes straight to nvm and 3 and 4 take turns pushing each other out
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(tier_accesses[1].demotions , Vec::new());
+        assert_eq!(results[2] , AccessResult::MissReplace(MissReplacement::new(2, 0, 3)));
+        assert_eq!(results[3] , AccessResult::MissReplace(MissReplacement::new(3, 0, 4)));
+        assert!(tier_accesses.iter().all(|a| a.demotions.iter().all(|m| m.page != 1)));
+    }
+
+    #[test]
+    fn victim_goes_to_swap_when_the_tier_below_is_all_pinned() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 L:1 R:3 R:2"));
+        let (results, tier_accesses) = process_page_requests_with_tiers(&tiers("dram:1:100:lru,nvm:1:300:lru"), 10, accesses).unwrap();
+        assert_eq!(results[3] , AccessResult::MissReplace(MissReplacement::new(2, 0, 3)));
+        assert_eq!(tier_accesses[3].demotions , vec![Migration { page : 2, from : 0, to : None }]);
+        assert_eq!(results[4] , AccessResult::MissReplace(MissReplacement::new(3, 0, 2)));
+    }
+
+    #[test]
+    fn every_tier_pinned_is_an_error() {
+        let accesses = MemoryAccess::create(String::from("L:1 L:2 R:3"));
+        let error = process_page_requests(&tiers("dram:1:100:lru,nvm:1:300:lru"), 10, accesses).unwrap_err();
+        assert_eq!(error , "All 2 frames are pinned, nothing can be evicted for page 3");
+    }
+
+    #[test]
+    fn bad_tiers_are_rejected() {
+        assert!(parse_tiers(Some(&String::from("dram:0:100:lru"))).is_err());
+        assert!(parse_tiers(Some(&String::from("dram:4:100:mru"))).is_err());
+        assert!(parse_tiers(Some(&String::from("dram:4:100"))).is_err());
+        assert!(parse_tiers(None).is_err());
+    }
+}
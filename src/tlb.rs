@@ -0,0 +1,286 @@
+// A TLB in front of the page table. It sits on top of any replacement algorithm: the algorithm
+// decides what's resident, and we replay its results to see which lookups the TLB would have
+// answered on its own. The TLB is split into sets of `ways` entries each (entries / ways sets, a
+// page always goes in set page % sets), and every set evicts with its own policy. When the
+// algorithm evicts a page its TLB entry is shot down, so the TLB never maps a page that's gone.
+use random::Random;
+use {exit_on_error, parse_file, parse_nonzero, parse_number};
+use {AccessResult, MemoryAccess};
+
+// Latencies in nanoseconds, for the effective access time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Latencies {
+    pub tlb : usize,
+    pub memory : usize,
+    // Servicing a page fault from disk
+    pub fault : usize,
+}
+
+pub const DEFAULT_LATENCIES : Latencies = Latencies { tlb : 1, memory : 100, fault : 8_000_000 };
+// The random TLB policy always starts from here so runs can be repeated
+pub const RANDOM_SEED : u64 = 4017;
+
+// How a full TLB set picks the entry to replace
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TlbPolicy {
+    Lru,
+    Fifo,
+    Random,
+}
+
+pub fn parse_tlb_policy(policy_string : Option<&String>) -> Result<TlbPolicy, String> {
+    match policy_string.map(|s| s.as_str()) {
+        None => Err(String::from("No TLB policy provided")),
+        Some("lru") => Ok(TlbPolicy::Lru),
+        Some("fifo") => Ok(TlbPolicy::Fifo),
+        Some("random") => Ok(TlbPolicy::Random),
+        Some(p) => Err(format!("TLB policy must be lru, fifo or random, not {}", p)),
+    }
+}
+
+// TLB parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct TLB_Params {
+    // Name of the page replacement algorithm underneath, as in compare::algorithms
+    pub algorithm : String,
+    pub total_frames : usize,
+    pub tau : usize,
+    pub entries : usize,
+    pub ways : usize,
+    pub policy : TlbPolicy,
+    pub access_string : String,
+}
+
+impl TLB_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> TLB_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let algorithm = exit_on_error(args.get(1).cloned().ok_or_else(|| String::from("No algorithm provided")));
+        let frames = exit_on_error(parse_number("frame count", args.get(2)));
+        let tau = exit_on_error(parse_number("tau", args.get(3)));
+        let entries = exit_on_error(parse_nonzero("TLB entry count", args.get(4)));
+        let ways = exit_on_error(parse_nonzero("associativity", args.get(5)).and_then(|w| {
+            if entries.is_multiple_of(w) { Ok(w) } else { Err(String::from("The TLB entry count has to be a multiple of the associativity")) }
+        }));
+        let policy = exit_on_error(parse_tlb_policy(args.get(6)));
+        let file = exit_on_error(parse_file(args.get(7)));
+        TLB_Params {
+            algorithm,
+            total_frames : frames,
+            tau,
+            entries,
+            ways,
+            policy,
+            access_string : file,
+        }
+    }
+}
+
+// What the TLB did on one access
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlbAccess {
+    pub hit : bool,
+    // The page whose entry was invalidated because the algorithm evicted it
+    pub shootdown : Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub page : usize,
+    pub inserted : usize,
+    pub last_used : usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tlb {
+    pub sets : Vec<Vec<Entry>>,
+    pub ways : usize,
+    pub policy : TlbPolicy,
+    random : Random,
+}
+
+impl Tlb {
+    // Constructor, entries has to be a multiple of ways
+    pub fn new(entries : usize , ways : usize , policy : TlbPolicy) -> Tlb {
+        Tlb {
+            sets : vec![Vec::with_capacity(ways); entries / ways],
+            ways,
+            policy,
+            random : Random::new(RANDOM_SEED),
+        }
+    }
+
+    fn set(&self , page : usize) -> usize {
+        page % self.sets.len()
+    }
+
+    // True if the page is mapped, and it counts as used
+    pub fn lookup(&mut self , page : usize , time : usize) -> bool {
+        let set = self.set(page);
+        match self.sets[set].iter_mut().find(|e| e.page == page) {
+            Some(entry) => {
+                entry.last_used = time;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn insert(&mut self , page : usize , time : usize) {
+        let set = self.set(page);
+        let entry = Entry { page, inserted : time, last_used : time };
+        if self.sets[set].len() < self.ways {
+            self.sets[set].push(entry);
+            return;
+        }
+        let index = match self.policy {
+            TlbPolicy::Lru => self.sets[set].iter().enumerate().min_by_key(|&(_, e)| e.last_used).map(|(i, _)| i).unwrap(),
+            TlbPolicy::Fifo => self.sets[set].iter().enumerate().min_by_key(|&(_, e)| e.inserted).map(|(i, _)| i).unwrap(),
+            TlbPolicy::Random => self.random.below(self.ways),
+        };
+        self.sets[set][index] = entry;
+    }
+
+    // Shootdown, true if there was an entry to throw away
+    pub fn invalidate(&mut self , page : usize) -> bool {
+        let set = self.set(page);
+        match self.sets[set].iter().position(|e| e.page == page) {
+            Some(i) => {
+                self.sets[set].remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+// Replays the algorithm's results through the TLB
+pub fn simulate(tlb : &mut Tlb , accesses : &[MemoryAccess] , results : &[AccessResult]) -> Vec<TlbAccess> {
+    let mut tlb_accesses : Vec<TlbAccess> = Vec::with_capacity(accesses.len());
+    for (time, (access, result)) in accesses.iter().zip(results.iter()).enumerate() {
+        let page = access.frame_number;
+        // A free isn't a lookup, but a translation for a page that's gone has to be shot down
+        if *result == AccessResult::Freed {
+            let shootdown = if tlb.invalidate(page) { Some(page) } else { None };
+            tlb_accesses.push(TlbAccess { hit : false, shootdown });
+            continue;
+        }
+        if tlb.lookup(page, time) {
+            tlb_accesses.push(TlbAccess { hit : true, shootdown : None });
+            continue;
+        }
+        // Off to the page table, which may have to fault the page in and evict another one
+        let shootdown = match *result {
+            AccessResult::MissReplace(ref mr) if tlb.invalidate(mr.replaced) => Some(mr.replaced),
+            _ => None,
+        };
+        tlb.insert(page, time);
+        tlb_accesses.push(TlbAccess { hit : false, shootdown });
+    }
+    tlb_accesses
+}
+
+// Average time per access in nanoseconds. A TLB hit goes straight to memory, a miss first walks
+// the page table (walk_references memory reads, 1 for a flat table) and may have to wait for a
+// page fault on top. Frees aren't accesses and don't count.
+pub fn effective_access_time(latencies : &Latencies , walk_references : usize , results : &[AccessResult] , tlb_accesses : &[TlbAccess]) -> f64 {
+    let count = results.iter().filter(|r| **r != AccessResult::Freed).count();
+    if count == 0 {
+        return 0.0;
+    }
+    let total : usize = results.iter().zip(tlb_accesses.iter()).filter(|&(result, _)| *result != AccessResult::Freed).map(|(result, tlb)| {
+        let mut time = latencies.tlb + latencies.memory;
+        if !tlb.hit {
+            time += walk_references * latencies.memory;
+            if result.is_fault() {
+                time += latencies.fault;
+            }
+        }
+        time
+    }).sum();
+    total as f64 / count as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compare::algorithm;
+    use MissReplacement;
+
+    #[test]
+    fn repeated_page_hits_the_tlb() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1 R:2"));
+        let results = vec![AccessResult::MissSimple, AccessResult::MissSimple, AccessResult::Hit, AccessResult::Hit];
+        let mut tlb = Tlb::new(2, 2, TlbPolicy::Lru);
+        let tlb_accesses = simulate(&mut tlb, &accesses, &results);
+        let hits : Vec<bool> = tlb_accesses.iter().map(|t| t.hit).collect();
+        assert_eq!(hits , vec![false, false, true, true]);
+    }
+
+    #[test]
+    fn evicted_page_is_shot_down() {
+        // With the TLB as big as memory the only way 1 can miss the TLB is the shootdown
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1"));
+        let results = (algorithm("second", 0).unwrap().run)(2, accesses.clone()).unwrap();
+        let mut tlb = Tlb::new(4, 4, TlbPolicy::Lru);
+        let tlb_accesses = simulate(&mut tlb, &accesses, &results);
+        assert_eq!(results[2] , AccessResult::MissReplace(MissReplacement::new(1, 0, 3)));
+        assert_eq!(tlb_accesses[2] , TlbAccess { hit : false, shootdown : Some(1) });
+        assert!(!tlb_accesses[3].hit);
+    }
+
+    #[test]
+    fn tlb_hit_always_means_the_page_is_resident() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1 R:4 R:2 R:1 R:5 R:3 R:1 R:2 R:4"));
+        for a in &["optimal", "second", "wsclock", "arc"] {
+            let results = (algorithm(a, 3).unwrap().run)(3, accesses.clone()).unwrap();
+            for policy in &[TlbPolicy::Lru, TlbPolicy::Fifo, TlbPolicy::Random] {
+                let mut tlb = Tlb::new(4, 2, *policy);
+                for (t, r) in simulate(&mut tlb, &accesses, &results).iter().zip(results.iter()) {
+                    if t.hit {
+                        assert_eq!(*r , AccessResult::Hit);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sets_evict_with_their_own_policy() {
+        // One set of two ways, 1 is used again after 2 comes in
+        let mut lru = Tlb::new(2, 2, TlbPolicy::Lru);
+        let mut fifo = Tlb::new(2, 2, TlbPolicy::Fifo);
+        for tlb in [&mut lru, &mut fifo] {
+            tlb.insert(1, 0);
+            tlb.insert(2, 1);
+            tlb.lookup(1, 2);
+            tlb.insert(3, 3);
+        }
+        assert!(lru.lookup(1, 4) && !lru.lookup(2, 4));
+        assert!(!fifo.lookup(1, 4) && fifo.lookup(2, 4));
+
+        // Two sets of one way, odd and even pages don't push each other out
+        let mut direct = Tlb::new(2, 1, TlbPolicy::Lru);
+        direct.insert(1, 0);
+        direct.insert(2, 1);
+        assert!(direct.lookup(1, 2) && direct.lookup(2, 2));
+        direct.insert(3, 3);
+        assert!(!direct.lookup(1, 4) && direct.lookup(2, 4));
+    }
+
+    #[test]
+    fn effective_access_time_adds_up_both_levels() {
+        let latencies = Latencies { tlb : 1, memory : 10, fault : 1000 };
+        let results = vec![AccessResult::MissSimple, AccessResult::Hit, AccessResult::Hit];
+        let tlb_accesses = vec![
+            TlbAccess { hit : false, shootdown : None },
+            TlbAccess { hit : true, shootdown : None },
+            TlbAccess { hit : false, shootdown : None },
+        ];
+        // 1 + 10 + 10 + 1000, then 1 + 10, then 1 + 10 + 10
+        assert_eq!(effective_access_time(&latencies, 1, &results, &tlb_accesses) , (1021.0 + 11.0 + 21.0) / 3.0);
+        // A 4 level walk costs 30 more on each TLB miss
+        assert_eq!(effective_access_time(&latencies, 4, &results, &tlb_accesses) , (1051.0 + 11.0 + 51.0) / 3.0);
+    }
+}
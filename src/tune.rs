@@ -0,0 +1,112 @@
+// Finds a good tau for WSClock by running it with every tau that can make a difference
+use wsclock;
+use {exit_on_error, get_total_faults, parse_file, parse_number};
+use MemoryAccess;
+
+// Tau tuning parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct TUNE_Params {
+    pub total_frames : usize,
+    // How many faults a single write back is worth, 0 means only faults count
+    pub write_cost : usize,
+    pub access_string : String,
+}
+
+impl TUNE_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> TUNE_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let frames = exit_on_error(parse_number("frame count", args.get(1)));
+        let write_cost = exit_on_error(parse_number("write cost", args.get(2)));
+        let file = exit_on_error(parse_file(args.get(3)));
+        TUNE_Params {
+            total_frames : frames,
+            write_cost,
+            access_string : file,
+        }
+    }
+}
+
+// How WSClock did with one particular tau
+#[derive(Debug, Clone, PartialEq)]
+pub struct TauPoint {
+    pub tau : usize,
+    pub faults : usize,
+    pub writes : usize,
+    pub cost : usize,
+}
+
+// Tau only matters through the ages the hand compares it to, so a run tells us the next tau that
+// can make any difference and we jump straight there. Every point stands for the taus from its
+// own up to the next point's. The curve isn't monotonic either, which is why we sweep everything
+// rather than binary search it.
+pub fn sweep_tau(total_frames : usize , write_cost : usize , accesses : &[MemoryAccess]) -> Result<Vec<TauPoint>, String> {
+    let mut curve : Vec<TauPoint> = Vec::new();
+    let mut tau = 0;
+    loop {
+        let v_memory = Vec::with_capacity(total_frames);
+        let (results, writes, next_age) = wsclock::process_page_requests_with_ages(tau, total_frames, accesses.to_vec(), v_memory)?;
+        let faults = get_total_faults(&results);
+        curve.push(TauPoint {
+            tau,
+            faults,
+            writes : writes.len(),
+            cost : faults + write_cost * writes.len(),
+        });
+        match next_age {
+            Some(age) => tau = age,
+            None => return Ok(curve),
+        }
+    }
+}
+
+// Lowest cost wins, ties go to the smallest tau
+pub fn best_tau(curve : &[TauPoint]) -> Option<&TauPoint> {
+    curve.iter().min_by_key(|p| (p.cost, p.tau))
+}
+
+pub fn curve_table(curve : &[TauPoint]) -> String {
+    let mut table = format!("{:>6} {:>8} {:>8} {:>8}\n", "tau", "faults", "writes", "cost");
+    for p in curve {
+        table.push_str(&format!("{:>6} {:>8} {:>8} {:>8}\n", p.tau, p.faults, p.writes, p.cost));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweep_matches_running_every_tau_below_trace_length() {
+        let accesses = MemoryAccess::create(String::from("R:1 W:2 R:3 W:2 W:4 R:4 R:4 R:5 R:1 W:3 R:6 R:2 R:5 R:1"));
+        let curve = sweep_tau(3, 0, &accesses).unwrap();
+        assert_eq!(curve[0].tau , 0);
+        assert!(curve.len() < accesses.len());
+        for tau in 0..accesses.len() {
+            let p = curve.iter().rev().find(|p| p.tau <= tau).unwrap();
+            let (results, writes) = wsclock::process_page_requests_with_writes(tau, 3, accesses.clone(), Vec::new()).unwrap();
+            assert_eq!(p.faults , get_total_faults(&results));
+            assert_eq!(p.writes , writes.len());
+            assert_eq!(p.cost , p.faults);
+        }
+    }
+
+    #[test]
+    fn write_cost_is_added_per_write_back() {
+        let accesses = MemoryAccess::create(String::from("R:1 W:2 R:3 W:2 W:4 R:4 R:4 R:5"));
+        let curve = sweep_tau(3, 10, &accesses).unwrap();
+        for p in &curve {
+            assert_eq!(p.cost , p.faults + 10 * p.writes);
+        }
+    }
+
+    #[test]
+    fn best_tau_prefers_lowest_cost_then_smallest_tau() {
+        let point = |tau, cost| TauPoint { tau, faults : cost, writes : 0, cost };
+        let curve = vec![point(0, 7), point(1, 5), point(2, 6), point(3, 5)];
+        assert_eq!(best_tau(&curve).unwrap().tau , 1);
+        assert_eq!(best_tau(&[]) , None);
+    }
+}
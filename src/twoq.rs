@@ -0,0 +1,155 @@
+// 2Q: new pages go into A1in, a FIFO. When they're pushed out of it we remember them in A1out (a
+// ghost list, no frames). A page that comes back while it's still in A1out was worth keeping,
+// so it goes into Am, a regular LRU. One-off pages never make it past A1in.
+// Dirty pages are written back before their frame is reclaimed.
+use std::collections::VecDeque;
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+pub fn process_page_requests(total_physical_pages : usize , accesses : Vec<MemoryAccess> , v_memory : Vec<usize>) -> Result<Vec<AccessResult>, String> {
+    Ok(process_page_requests_with_writes(total_physical_pages, accesses, v_memory)?.0)
+}
+
+// Same as above, but also hands back every page that was written back, in order
+pub fn process_page_requests_with_writes(total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<usize>) -> Result<(Vec<AccessResult>, Vec<usize>), String> {
+    // The sizes the paper recommends, a quarter of memory for A1in and ghosts for half of it
+    let k_in = (total_physical_pages / 4).max(1);
+    let k_out = (total_physical_pages / 2).max(1);
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut writes : Vec<usize> = Vec::new();
+    // Oldest at the front for all three
+    let mut a1_in : VecDeque<Page> = VecDeque::new();
+    let mut a1_out : VecDeque<usize> = VecDeque::new();
+    let mut am : VecDeque<Page> = VecDeque::new();
+    let mut pins = Pins::new();
+
+    for access in accesses.iter() {
+        pins.update(access);
+        let x = access.frame_number;
+        let is_write = access.access_type == AccessType::Write;
+        // A freed page is dropped from wherever it is without a write back, A1out forgets it too
+        if access.access_type == AccessType::Free {
+            am.retain(|p| p.number != x);
+            a1_in.retain(|p| p.number != x);
+            a1_out.retain(|&p| p != x);
+            v_memory.retain(|&p| p != x);
+            results.push(AccessResult::Freed);
+            continue;
+        }
+        if let Some(i) = am.iter().position(|p| p.number == x) {
+            let mut page = am.remove(i).unwrap();
+            page.is_dirty |= is_write;
+            am.push_back(page);
+            results.push(AccessResult::Hit);
+            continue;
+        }
+        if let Some(page) = a1_in.iter_mut().find(|p| p.number == x) {
+            // Correlated references while it's in A1in don't move it
+            page.is_dirty |= is_write;
+            results.push(AccessResult::Hit);
+            continue;
+        }
+
+        // Has to be checked before reclaiming, which could push it out of A1out
+        let remembered = match a1_out.iter().position(|&p| p == x) {
+            Some(i) => {
+                a1_out.remove(i);
+                true
+            }
+            None => false,
+        };
+
+        if v_memory.len() < total_physical_pages {
+            v_memory.push(x);
+            results.push(AccessResult::MissSimple);
+        } else {
+            // The oldest page that isn't pinned, from A1in unless it's small enough and Am has one
+            pins.check_evictable(total_physical_pages, x)?;
+            let in_a1 = a1_in.iter().position(|p| !pins.contains(p.number));
+            let in_am = am.iter().position(|p| !pins.contains(p.number));
+            let victim = match in_a1 {
+                Some(i) if a1_in.len() > k_in || in_am.is_none() => {
+                    let victim = a1_in.remove(i).unwrap();
+                    a1_out.push_back(victim.number);
+                    if a1_out.len() > k_out {
+                        a1_out.pop_front();
+                    }
+                    victim
+                }
+                _ => am.remove(in_am.unwrap()).unwrap(),
+            };
+            if victim.is_dirty {
+                writes.push(victim.number);
+            }
+            let index = v_memory.iter().position(|&p| p == victim.number).unwrap();
+            results.push(AccessResult::MissReplace(MissReplacement::new(victim.number, index, x)));
+            v_memory[index] = x;
+        }
+
+        let page = Page { number : x, is_dirty : is_write };
+        if remembered {
+            am.push_back(page);
+        } else {
+            a1_in.push_back(page);
+        }
+    }
+
+    Ok((results, writes))
+}
+
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub number : usize,
+    pub is_dirty : bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn a1in_is_a_fifo_even_with_hits() {
+        // 4 frames means A1in holds 1 before it starts giving pages up, 1 is hit but still goes first
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:1 R:5"));
+        let total_pages = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[4] , AccessResult::Hit);
+        let mr = MissReplacement::new(1, 0, 5);
+        assert_eq!(results[5] , AccessResult::MissReplace(mr));
+    }
+
+    #[test]
+    fn page_back_from_a1out_goes_to_am_and_survives_a_scan() {
+        // 1 is pushed out into A1out by 5 and 6, comes back and lands in Am, after that the
+        // scan of 7, 8, 9 only churns through A1in
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:4 R:5 R:6 R:1 R:7 R:8 R:9 R:1"));
+        let total_pages = 4;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(total_pages, accesses, v_memory).unwrap();
+        assert_ne!(results[6] , AccessResult::Hit);
+        assert_eq!(results[10] , AccessResult::Hit);
+    }
+
+    #[test]
+    fn dirty_victims_are_written_back() {
+        let accesses = MemoryAccess::create(String::from("W:1 R:2 W:2 R:3 R:4"));
+        let total_pages = 2;
+        let v_memory : Vec<usize> = Vec::with_capacity(total_pages);
+        let (results, writes) = process_page_requests_with_writes(total_pages, accesses, v_memory).unwrap();
+        assert_eq!(get_total_faults(&results) , 4);
+        assert_eq!(writes , vec![1, 2]);
+    }
+}
@@ -0,0 +1,314 @@
+// WSClock: a circular list of frames where the hand evicts old, clean, unreferenced pages
+use {AccessResult, AccessType, MemoryAccess, MissReplacement, Pins};
+
+pub fn process_page_requests(tau : usize , total_physical_pages : usize , accesses : Vec<MemoryAccess> , v_memory : Vec<Page>)-> Result<Vec<AccessResult>, String> {
+    Ok(process_page_requests_with_writes(tau, total_physical_pages, accesses, v_memory)?.0)
+}
+
+// Same as above, but also hands back every page that was scheduled to be written to disk, in order
+pub fn process_page_requests_with_writes(tau : usize , total_physical_pages : usize , accesses : Vec<MemoryAccess> , v_memory : Vec<Page>)-> Result<(Vec<AccessResult>, Vec<Page>), String> {
+    let (results, writes, _) = process_page_requests_with_ages(tau, total_physical_pages, accesses, v_memory)?;
+    Ok((results, writes))
+}
+
+// Results, pages scheduled to be written, and the smallest age over tau that was seen
+pub type WithAges = (Vec<AccessResult>, Vec<Page>, Option<usize>);
+
+// Same as above, plus the smallest age over tau the hand compared against it. Every tau from this
+// one up to just below that age runs exactly the same, and None means every bigger tau does.
+pub fn process_page_requests_with_ages(tau : usize , total_physical_pages : usize , accesses : Vec<MemoryAccess> , mut v_memory : Vec<Page>)-> Result<WithAges, String> {
+    // The clock pointer!
+    let mut pointer = 0;
+    let mut results : Vec<AccessResult> = Vec::with_capacity(accesses.len());
+    let mut writes : Vec<Page> = Vec::new();
+    let mut next_age : Option<usize> = None;
+    let mut pins = Pins::new();
+
+    // Iterate over all the accesses in order, clock provides the age
+    for (clock, access) in accesses.iter().enumerate() {
+        pins.update(access);
+        let contained = contains_page(access.frame_number, &v_memory);
+        // A freed page comes off the clock without being written back, dirty or not, and the hand
+        // keeps pointing at the same page it did
+        if access.access_type == AccessType::Free {
+            if let Some(index) = contained {
+                pointer %= v_memory.len();
+                if index < pointer {
+                    pointer -= 1;
+                }
+                v_memory.remove(index);
+            }
+            results.push(AccessResult::Freed);
+            continue;
+        }
+        // Does the page exist?
+        if let Some(index) = contained {
+            v_memory[index].referenced = true;
+            // Update the timestamp!
+            v_memory[index].timestamp = clock;
+            if access.access_type == AccessType::Write {
+                v_memory[index].is_dirty = true;
+            }
+            results.push(AccessResult::Hit);
+        } else {
+            let length = v_memory.len();
+            let index;
+            // Crucially, here we check if we have space, if we do, it's a simple miss
+            // is_dirty is false because it's the first entry
+            if length < total_physical_pages {
+                v_memory.push(Page {
+                    number : access.frame_number,
+                    timestamp : clock,
+                    is_dirty : false,
+                    referenced : true, });
+                results.push(AccessResult::MissSimple);
+            } else {
+                // With every frame pinned the loop below would never find anything
+                pins.check_evictable(total_physical_pages, access.frame_number)?;
+                // Remember to modulo so we can loop around, it gets tedious though...
+                // start_pointer and iteration in conjunction help us keep track of whether we're
+                // back to the beginning
+                let start_pointer = pointer % length;
+                let mut iteration = 0;
+                // We are basically going to loop until either the age is greater than tau, or
+                // we have made it to the second iteration
+                loop {
+                    let page = &mut v_memory[pointer % length];
+                    if pins.contains(page.number) {
+                        // Pinned, the hand just goes past it
+                    } else if page.referenced {
+                        // It's referenced! Remove reference...
+                        page.referenced = false;
+                    } else {
+                        let age = clock - page.timestamp;
+                        if age > tau {
+                            next_age = Some(next_age.map_or(age, |next| next.min(age)));
+                            // If it's old and clean, give it the index of the page we're going to evict
+                            if !page.is_dirty {
+                                index = pointer % length;
+                                break;
+                            }
+                        } else if iteration > 0 {
+                            // If it's second iteration and clean,
+                            // give it the index of the page we're going to evict
+                            if !page.is_dirty {
+                                index = pointer % length;
+                                break;
+                            }
+                        }
+                        // Always schedule a write to disk... according to the algorithm
+                        // the is_dirty flag would get set asynchronously, probably by some
+                        // interrupt, but we don't have that, although we could just create a
+                        // child thread to change it but that's complicated
+                        if page.is_dirty {
+                            schedule_write_to_disk(page.clone(), &mut writes);
+                            page.is_dirty = false;
+                        }
+                    }
+                    pointer += 1;
+                    if start_pointer == pointer % length {
+                        iteration += 1;
+                    }
+                }
+                // Finally! Handle the replacement using the index we found, evict and push!
+                results.push(AccessResult::MissReplace(
+                    MissReplacement::new(
+                        v_memory[index].number,
+                        index,
+                        access.frame_number)));
+                v_memory[index] = Page {
+                    number : access.frame_number,
+                    timestamp : clock,
+                    is_dirty : false,
+                    referenced : true };
+            }
+        }
+    }
+
+    Ok((results, writes, next_age))
+}
+
+// Just fake it! The caller decides what to do with the pages that would've been written
+fn schedule_write_to_disk(page : Page , writes : &mut Vec<Page>) {
+    writes.push(page);
+}
+
+fn contains_page(page_num : usize , collection : &[Page]) -> Option<usize> {
+    for (i, item) in collection.iter().enumerate() {
+        if page_num == item.number {
+            return Some(i);
+        }
+    }
+    None
+}
+
+// Even more complex data structure
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub number : usize,
+    pub referenced : bool,
+    pub is_dirty : bool,
+    pub timestamp : usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use get_total_faults;
+
+    #[test]
+    fn three_initial_accesses_are_all_simple_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 4;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(5, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn third_miss_is_miss_replace() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 W:3"));
+        let total_pages = 2;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(5, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+
+        // Replaced R:1 at index 0 with R:3
+        let mr = MissReplacement::new(1, 0, 3);
+        assert_eq!(results[2] , AccessResult::MissReplace(mr));
+
+        assert_eq!(get_total_faults(&results) , 3);
+    }
+
+    #[test]
+    fn all_are_subsequent_hits_after_first_miss() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:1 W:1 R:1 W:1"));
+        let total_pages = 2;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(5, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        for result in results.iter().skip(1) {
+            assert_eq!(*result , AccessResult::Hit);
+        }
+        assert_eq!(get_total_faults(&results) , 1);
+    }
+
+    #[test]
+    fn alternating_hits_and_misses() {
+        let accesses = MemoryAccess::create(String::from("R:1 W:1 W:2 R:1 R:2 W:3 W:4"));
+        let total_pages = 4;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(5, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::Hit);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(results[3] , AccessResult::Hit);
+        assert_eq!(results[4] , AccessResult::Hit);
+        assert_eq!(results[5] , AccessResult::MissSimple);
+        assert_eq!(results[6] , AccessResult::MissSimple);
+        assert_eq!(results.len() , 7);
+        assert_eq!(get_total_faults(&results) , 4);
+    }
+
+    #[test]
+    fn evicts_first_then_four_since_two_and_three_are_later_referenced() {
+        let accesses = MemoryAccess::create(String::from("R:1 W:2 W:3 R:4 R:2 W:3 W:5"));
+        let total_pages = 3;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(5, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(results[4] , AccessResult::Hit);
+        assert_eq!(results[5] , AccessResult::Hit);
+
+        // Replaced R:1 at index 0, pushed R:4
+        let mr = MissReplacement::new(1, 0, 4);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+
+        // Replaced R:1 at index 0, pushed R:4
+        let mr = MissReplacement::new(4, 0, 5);
+        assert_eq!(results[6] , AccessResult::MissReplace(mr));
+
+        assert_eq!(get_total_faults(&results) , 5);
+    }
+
+    #[test]
+    fn evicts_one_and_four_because_age_is_old_enough() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 W:4 W:2 R:5"));
+        let total_pages = 3;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(5, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(results[4] , AccessResult::Hit);
+
+        // Replaced R:1 at index 0, pushed R:4
+        let mr = MissReplacement::new(1, 0, 4);
+        assert_eq!(results[3] , AccessResult::MissReplace(mr));
+
+        // Replaced R:4 at index 0, pushed R:5
+        let mr = MissReplacement::new(4, 0, 5);
+        assert_eq!(results[5] , AccessResult::MissReplace(mr));
+
+        assert_eq!(get_total_faults(&results) , 5);
+    }
+
+    #[test]
+    fn skips_dirty_page_two_for_clean_three_when_age_is_old() {
+        let accesses = MemoryAccess::create(String::from("R:1 W:2 R:3 W:2 W:4 R:4 R:4 R:5"));
+        let total_pages = 3;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let results = process_page_requests(3, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(results[0] , AccessResult::MissSimple);
+        assert_eq!(results[1] , AccessResult::MissSimple);
+        assert_eq!(results[2] , AccessResult::MissSimple);
+        assert_eq!(results[3] , AccessResult::Hit);
+        assert_eq!(results[5] , AccessResult::Hit);
+        assert_eq!(results[6] , AccessResult::Hit);
+
+        // Replaced R:1 at index 0, pushed R:4
+        let mr = MissReplacement::new(1, 0, 4);
+        assert_eq!(results[4] , AccessResult::MissReplace(mr));
+
+        // Replaced R:3 at index 2, pushed R:5
+        let mr = MissReplacement::new(3, 2, 5);
+        assert_eq!(results[7] , AccessResult::MissReplace(mr));
+
+        assert_eq!(get_total_faults(&results) , 5);
+    }
+
+    #[test]
+    fn dirty_page_is_written_before_it_can_be_evicted() {
+        let accesses = MemoryAccess::create(String::from("R:1 W:2 R:3 W:2 W:4 R:4 R:4 R:5"));
+        let total_pages = 3;
+        let v_memory : Vec<Page> = Vec::with_capacity(total_pages);
+        let (results, writes) = process_page_requests_with_writes(3, total_pages, accesses, v_memory).unwrap();
+        assert_eq!(get_total_faults(&results) , 5);
+        // Page 2 gets skipped over and scheduled while looking for a victim for R:5
+        assert_eq!(writes.len() , 1);
+        assert_eq!(writes[0].number , 2);
+    }
+
+    #[test]
+    fn every_frame_pinned_stops_instead_of_going_around_forever() {
+        let accesses = MemoryAccess::create(String::from("L:1 L:2 R:3"));
+        let v_memory : Vec<Page> = Vec::with_capacity(2);
+        let error = process_page_requests(0, 2, accesses, v_memory).unwrap_err();
+        assert!(error.starts_with("All 2 frames are pinned"));
+    }
+
+    #[test]
+    fn hand_goes_past_pinned_pages() {
+        // 1 is the oldest and unreferenced, but pinned, so 2 goes instead
+        let accesses = MemoryAccess::create(String::from("L:1 R:2 R:3"));
+        let v_memory : Vec<Page> = Vec::with_capacity(2);
+        let results = process_page_requests(0, 2, accesses, v_memory).unwrap();
+        assert_eq!(results[2] , AccessResult::MissReplace(MissReplacement::new(2, 1, 3)));
+    }
+}
@@ -0,0 +1,266 @@
+// A compressed pool between memory and disk, like Linux's zswap. It sits underneath any replacement
+// algorithm by replaying its results: a page the algorithm evicts is compressed into the pool
+// instead of going to disk, and faulting it back in decompresses it from there, which is a lot
+// cheaper than a disk read. The pool holds pool_frames frames worth of compressed data, a page
+// taking 1 / ratio of a frame, and when it's full the pages that went in first are written out to
+// disk to make room. A page that doesn't compress into the whole pool goes straight to disk.
+// The ratio comes from the command line, or per page from the trace as a z field, R:7:z=3.5 is
+// page 7 compressing 3.5 to 1. A page is compressed with the last ratio the trace gave it.
+use std::collections::{HashMap, VecDeque};
+use {access_field, exit_on_error, parse_file, parse_number};
+use {AccessResult, MemoryAccess};
+
+// Nanoseconds to decompress a page from the pool, a disk read is tlb::DEFAULT_LATENCIES.fault
+pub const POOL_LATENCY : usize = 10_000;
+
+// Compression ratios have to be at least 1, nothing gets bigger
+pub fn parse_ratio(ratio_string : Option<&String>) -> Result<f64, String> {
+    match ratio_string.map(|r| r.parse::<f64>()) {
+        None => Err(String::from("No compression ratio provided")),
+        Some(Ok(r)) if r >= 1.0 => Ok(r),
+        Some(_) => Err(String::from("The compression ratio has to be a number of at least 1")),
+    }
+}
+
+// The ratio of every access, the default one where the trace doesn't give any. A range free is
+// one access per page, like MemoryAccess::create makes it.
+pub fn parse_ratios(input_string : &str , default_ratio : f64) -> Result<Vec<f64>, String> {
+    let mut ratios : Vec<f64> = Vec::new();
+    for token in input_string.split_whitespace() {
+        let mut fields = token.split(':');
+        let pages = match (fields.next(), fields.next().and_then(|n| n.split_once('-'))) {
+            (Some("F"), Some((first, last))) => match (first.parse::<usize>(), last.parse::<usize>()) {
+                (Ok(first), Ok(last)) => (last + 1).saturating_sub(first),
+                _ => return Err(format!("Invalid page range in {}", token)),
+            },
+            _ => 1,
+        };
+        let ratio = match access_field(token, "z")? {
+            Some(r) => parse_ratio(Some(&r.to_string())).map_err(|e| format!("{} in {}", e, token))?,
+            None => default_ratio,
+        };
+        ratios.extend(std::iter::repeat_n(ratio, pages));
+    }
+    Ok(ratios)
+}
+
+// Compressed pool parameters from the CLI args
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone)]
+pub struct ZSWAP_Params {
+    pub algorithm : String,
+    pub total_frames : usize,
+    pub tau : usize,
+    pub pool_frames : usize,
+    pub ratio : f64,
+    pub access_string : String,
+}
+
+impl ZSWAP_Params {
+    // Constructor collecting CLI args directly, emitting errors in the process
+    pub fn get() -> ZSWAP_Params {
+        let args : Vec<String> = std::env::args().collect();
+        let algorithm = exit_on_error(args.get(1).cloned().ok_or_else(|| String::from("No algorithm provided")));
+        let frames = exit_on_error(parse_number("frame count", args.get(2)));
+        let tau = exit_on_error(parse_number("tau", args.get(3)));
+        let pool_frames = exit_on_error(parse_number("pool frame count", args.get(4)));
+        let ratio = exit_on_error(parse_ratio(args.get(5)));
+        let file = exit_on_error(parse_file(args.get(6)));
+        ZSWAP_Params {
+            algorithm,
+            total_frames : frames,
+            tau,
+            pool_frames,
+            ratio,
+            access_string : file,
+        }
+    }
+}
+
+// Where an access found its page
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Memory,
+    Pool,
+    Disk,
+    // A free, nothing was read
+    Nowhere,
+}
+
+// What the pool did on one access
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolAccess {
+    pub source : Source,
+    // Pages written out of the pool to disk, oldest first
+    pub written_back : Vec<usize>,
+    // Frames the pool saves right after this access, what its pages would take uncompressed
+    // minus what they do take
+    pub saved_frames : f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pool {
+    pub capacity : f64,
+    pub used : f64,
+    // Pages in the order they went in, with their compressed size in frames
+    pub pages : VecDeque<(usize, f64)>,
+}
+
+impl Pool {
+    pub fn new(frames : usize) -> Pool {
+        Pool { capacity : frames as f64, used : 0.0, pages : VecDeque::new() }
+    }
+
+    // True if the page was in the pool, it's taken out either way
+    pub fn load(&mut self , page : usize) -> bool {
+        match self.pages.iter().position(|&(p, _)| p == page) {
+            Some(i) => {
+                let (_, size) = self.pages.remove(i).unwrap();
+                self.used -= size;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Compresses the page in, gives back what had to go to disk for it, the page itself when it
+    // can't fit at all
+    pub fn store(&mut self , page : usize , ratio : f64) -> Vec<usize> {
+        let size = 1.0 / ratio;
+        if size > self.capacity {
+            return vec![page];
+        }
+        let mut written_back : Vec<usize> = Vec::new();
+        while self.used + size > self.capacity {
+            let (p, s) = self.pages.pop_front().unwrap();
+            self.used -= s;
+            written_back.push(p);
+        }
+        self.pages.push_back((page, size));
+        self.used += size;
+        written_back
+    }
+
+    pub fn saved_frames(&self) -> f64 {
+        self.pages.len() as f64 - self.used
+    }
+}
+
+// Replays the algorithm's results through the pool. The evicted page is known by number, not by
+// frame_index, since not every algorithm fills that in the same way.
+pub fn simulate(pool_frames : usize , accesses : &[MemoryAccess] , results : &[AccessResult] , ratios : &[f64]) -> Vec<PoolAccess> {
+    let mut pool = Pool::new(pool_frames);
+    let mut page_ratios : HashMap<usize, f64> = HashMap::new();
+    accesses.iter().zip(results.iter()).zip(ratios.iter()).map(|((access, result), &ratio)| {
+        page_ratios.insert(access.frame_number, ratio);
+        let mut written_back : Vec<usize> = Vec::new();
+        let source = match *result {
+            AccessResult::Hit | AccessResult::PrefetchHit => Source::Memory,
+            AccessResult::Freed => {
+                // Whatever the pool still had of it is thrown away
+                pool.load(access.frame_number);
+                Source::Nowhere
+            }
+            _ => {
+                if let AccessResult::MissReplace(ref mr) = *result {
+                    written_back = pool.store(mr.replaced, page_ratios[&mr.replaced]);
+                }
+                if pool.load(access.frame_number) { Source::Pool } else { Source::Disk }
+            }
+        };
+        PoolAccess { source, written_back, saved_frames : pool.saved_frames() }
+    }).collect()
+}
+
+// Totals over a whole run
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolSummary {
+    pub pool_hits : usize,
+    pub disk_reads : usize,
+    pub written_back : usize,
+    pub peak_saved_frames : f64,
+    pub average_saved_frames : f64,
+    // Average nanoseconds to service a fault, from the pool or from disk
+    pub average_fault_latency : f64,
+}
+
+pub fn summarize(pool_accesses : &[PoolAccess] , disk_latency : usize) -> PoolSummary {
+    let pool_hits = pool_accesses.iter().filter(|a| a.source == Source::Pool).count();
+    let disk_reads = pool_accesses.iter().filter(|a| a.source == Source::Disk).count();
+    let faults = pool_hits + disk_reads;
+    let count = pool_accesses.len().max(1) as f64;
+    PoolSummary {
+        pool_hits,
+        disk_reads,
+        written_back : pool_accesses.iter().map(|a| a.written_back.len()).sum(),
+        peak_saved_frames : pool_accesses.iter().map(|a| a.saved_frames).fold(0.0, f64::max),
+        average_saved_frames : pool_accesses.iter().map(|a| a.saved_frames).sum::<f64>() / count,
+        average_fault_latency : if faults == 0 {
+            0.0
+        } else {
+            (pool_hits * POOL_LATENCY + disk_reads * disk_latency) as f64 / faults as f64
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compare::algorithm;
+    use {get_total_faults, MissReplacement};
+
+    #[test]
+    fn evicted_page_faults_back_from_the_pool() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:1"));
+        let results = vec![
+            AccessResult::MissSimple,
+            AccessResult::MissReplace(MissReplacement::new(1, 0, 2)),
+            AccessResult::MissReplace(MissReplacement::new(2, 0, 1)),
+        ];
+        let pool_accesses = simulate(1, &accesses, &results, &[2.0, 2.0, 2.0]);
+        let sources : Vec<Source> = pool_accesses.iter().map(|a| a.source).collect();
+        assert_eq!(sources , vec![Source::Disk, Source::Disk, Source::Pool]);
+        // 1 left and 2 went in, both at half a frame
+        assert_eq!(pool_accesses[1].saved_frames , 0.5);
+        assert_eq!(pool_accesses[2].saved_frames , 0.5);
+    }
+
+    #[test]
+    fn full_pool_writes_its_oldest_pages_to_disk() {
+        let mut pool = Pool::new(1);
+        assert!(pool.store(1, 2.0).is_empty());
+        assert!(pool.store(2, 4.0).is_empty());
+        // 3 needs half a frame and there's only a quarter left
+        assert_eq!(pool.store(3, 2.0) , vec![1]);
+        assert_eq!(pool.used , 0.75);
+        assert!(!pool.load(1));
+        assert!(pool.load(2));
+        // Nothing fits in an empty pool
+        assert_eq!(Pool::new(0).store(4, 8.0) , vec![4]);
+    }
+
+    #[test]
+    fn ratios_come_from_the_trace_per_page() {
+        let ratios = parse_ratios("R:1:z=4 R:2 W:1:p=3:z=1.5", 2.0).unwrap();
+        assert_eq!(ratios , vec![4.0, 2.0, 1.5]);
+        assert!(parse_ratios("R:1:z=0.5", 2.0).is_err());
+        assert!(parse_ratios("R:1:z=x", 2.0).is_err());
+        assert!(parse_ratios("R:1:4", 2.0).is_err());
+        // A range free is one entry per page
+        assert_eq!(parse_ratios("R:1:z=4 F:1-3 R:2", 2.0).unwrap() , vec![4.0, 2.0, 2.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn pool_hits_replace_disk_reads_for_any_algorithm() {
+        let accesses = MemoryAccess::create(String::from("R:1 R:2 R:3 R:1 R:4 R:2 R:1 R:5 R:3 R:1 R:2 R:4"));
+        let ratios = vec![3.0; accesses.len()];
+        for a in &["optimal", "second", "arc", "lirs"] {
+            let results = (algorithm(a, 3).unwrap().run)(3, accesses.clone()).unwrap();
+            let summary = summarize(&simulate(2, &accesses, &results, &ratios), 1000);
+            // Only the first touch of each page has to go to disk with room for 6 compressed pages
+            assert_eq!(summary.disk_reads , 5);
+            assert_eq!(summary.pool_hits + summary.disk_reads , get_total_faults(&results));
+            assert_eq!(summary.written_back , 0);
+        }
+    }
+}